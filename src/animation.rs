@@ -0,0 +1,169 @@
+#![allow(unused)]
+
+use crate::compute::Viewport;
+use crate::math::Complex;
+
+/// A curve that shapes how an animation progresses over time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    /// Progress at a constant rate.
+    #[default]
+    Linear,
+    /// Start slowly and speed up towards the end.
+    EaseIn,
+    /// Start quickly and slow down towards the end.
+    EaseOut,
+    /// Start and end slowly, moving fastest in the middle.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the linear progress `t` in `[0, 1]` to the eased progress, which is also in `[0, 1]`.
+    #[inline]
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Interpolates between two viewports by `t`, with the scale interpolated in log-space.
+///
+/// Interpolating the logarithm of the scale makes every step zoom in by the same factor,
+/// instead of rushing through the first part of a deep zoom and crawling through the rest.
+pub fn interpolate_viewport(start: &Viewport, end: &Viewport, t: f32) -> Viewport {
+    let log_scale = start.scale.ln() + (end.scale.ln() - start.scale.ln()) * t;
+
+    Viewport {
+        center: start.center + (end.center - start.center) * t,
+        scale: log_scale.exp(),
+        stretch_x: start.stretch_x + (end.stretch_x - start.stretch_x) * t,
+    }
+}
+
+/// Creates the viewports of a `frames` long animation that zooms from `start` to `end`.
+///
+/// `easing` is applied to the progress of the zoom before it is interpolated in log-space, so the zoom
+/// can accelerate and decelerate. The first and last frames are always exactly `start` and `end`.
+pub fn zoom_frames(start: &Viewport, end: &Viewport, frames: u32, easing: Easing) -> Vec<Viewport> {
+    match frames {
+        0 => Vec::new(),
+        1 => vec![*start],
+        _ => (0..frames)
+            .map(|i| match i {
+                0 => *start,
+                i if i == frames - 1 => *end,
+                i => interpolate_viewport(start, end, easing.apply(i as f32 / (frames - 1) as f32)),
+            })
+            .collect(),
+    }
+}
+
+/// Creates a smooth camera path through `keys`, with `frames_per_segment` frames from each keyframe to the next.
+///
+/// The center, the logarithm of the scale and the stretch are each interpolated with a uniform Catmull-Rom spline,
+/// so the path passes through every keyframe and its direction of travel changes smoothly as it does. The first
+/// and last keyframes are repeated to give the spline its missing neighbors at the ends of the path.
+///
+/// Every segment starts exactly on its keyframe and the path ends exactly on the last one, for a total of
+/// `(keys.len() - 1) * frames_per_segment + 1` frames. A `frames_per_segment` of `0` is treated as `1`.
+pub fn interpolate_keyframes(keys: &[Viewport], frames_per_segment: u32) -> Vec<Viewport> {
+    let Some(last) = keys.last() else {
+        return Vec::new();
+    };
+
+    let frames_per_segment = frames_per_segment.max(1);
+    let mut frames = Vec::with_capacity((keys.len() - 1) * frames_per_segment as usize + 1);
+
+    for i in 0..keys.len() - 1 {
+        let p0 = &keys[i.saturating_sub(1)];
+        let p1 = &keys[i];
+        let p2 = &keys[i + 1];
+        let p3 = &keys[(i + 2).min(keys.len() - 1)];
+
+        frames.push(*p1);
+
+        for j in 1..frames_per_segment {
+            let t = j as f32 / frames_per_segment as f32;
+            let spline = |f: fn(&Viewport) -> f32| catmull_rom(f(p0), f(p1), f(p2), f(p3), t);
+
+            frames.push(Viewport {
+                center: Complex::new(spline(|v| v.center.re), spline(|v| v.center.im)),
+                scale: spline(|v| v.scale.ln()).exp(),
+                stretch_x: spline(|v| v.stretch_x),
+            });
+        }
+    }
+
+    frames.push(*last);
+    frames
+}
+
+/// Evaluates the uniform Catmull-Rom spline through `p1` and `p2` at `t`, with `p0` and `p3` as their outer neighbors.
+#[inline]
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ease_in_out_zoom_is_slow_at_both_ends() {
+        let start = Viewport::new(Complex::new(-0.5, 0.0), 1.25);
+        let end = Viewport::with_scales(Complex::new(-0.743_643_9, 0.131_825_9), 3e-5, 1e-5);
+        let frames = zoom_frames(&start, &end, 21, Easing::EaseInOut);
+        assert_eq!(frames.len(), 21);
+
+        for (frame, expected) in [(frames[0], start), (frames[20], end)] {
+            assert_eq!(frame.center.canonical_bits(), expected.center.canonical_bits());
+            assert_eq!((frame.scale, frame.stretch_x), (expected.scale, expected.stretch_x));
+        }
+
+        // The zoom speeds up steadily towards the middle and slows down again just as steadily.
+        let steps: Vec<f32> = frames.windows(2).map(|w| (w[1].scale.ln() - w[0].scale.ln()).abs()).collect();
+        assert!(steps[..10].windows(2).all(|w| w[0] < w[1]), "{steps:?}");
+        assert!(steps[10..].windows(2).all(|w| w[0] > w[1]), "{steps:?}");
+        assert!(steps[0] < steps[9] / 5.0 && steps[19] < steps[10] / 5.0, "{steps:?}");
+    }
+
+    #[test]
+    fn keyframe_path_is_smooth_through_every_key() {
+        let keys = [
+            Viewport::new(Complex::new(-0.5, 0.0), 1.25),
+            Viewport::new(Complex::new(-0.8, 0.2), 0.2),
+            Viewport::with_scales(Complex::new(-0.74, 0.12), 0.03, 0.01),
+            Viewport::new(Complex::new(-0.745, 0.11), 0.002),
+        ];
+        let frames = interpolate_keyframes(&keys, 20);
+        assert_eq!(frames.len(), 3 * 20 + 1);
+
+        for (i, key) in keys.iter().enumerate() {
+            let frame = frames[i * 20];
+            assert_eq!(frame.center.canonical_bits(), key.center.canonical_bits(), "key {i}");
+            assert_eq!((frame.scale, frame.stretch_x), (key.scale, key.stretch_x), "key {i}");
+        }
+
+        let channels: [fn(&Viewport) -> f32; 4] = [|v| v.center.re, |v| v.center.im, |v| v.scale.ln(), |v| v.stretch_x];
+        for channel in channels {
+            let values: Vec<f32> = frames.iter().map(channel).collect();
+            let steps: Vec<f32> = values.windows(2).map(|w| w[1] - w[0]).collect();
+            let largest_gap = keys.windows(2).map(|w| (channel(&w[1]) - channel(&w[0])).abs()).fold(0.0, f32::max);
+
+            // No single frame jumps much further than an even share of the widest segment,
+            // and the speed changes gradually from one frame to the next, including across keyframes.
+            for pair in steps.windows(2) {
+                assert!(pair[1].abs() <= largest_gap / 20.0 * 2.0, "{steps:?}");
+                assert!((pair[1] - pair[0]).abs() <= largest_gap / 20.0 * 0.25, "{steps:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,651 @@
+#![allow(unused)]
+
+use std::fmt;
+use std::num::ParseFloatError;
+
+use crate::escape::{
+    escape_time_with, segment_in_cardioid_or_bulb, smooth_escape_time, smooth_fraction, smooth_fraction_lut, EscapeNorm,
+    InteriorEarlyOut, BAILOUT_SQR,
+};
+use crate::field::Field;
+use crate::math::{Complex, ComplexF32, ComplexF64, Point};
+
+/// The half-height of the view shown by a fractal explorer at a zoom of `1`.
+const EXPLORER_BASE_RADIUS: f64 = 2.0;
+
+/// How far, in pixels, a pixel may land from the center of a pixel of the previous frame
+/// for [`reproject_counts`] to reuse that pixel's count.
+const REPROJECT_TOLERANCE: f32 = 1e-3;
+
+/// The region of the complex plane that is visible in a render.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Viewport {
+    /// The complex number at the center of the image.
+    pub center: ComplexF32,
+    /// Half of the height of the visible region, in complex units.
+    ///
+    /// The visible width follows from the aspect ratio of the image, see [`Viewport::visible_width`].
+    pub scale: f32,
+    /// How much the horizontal axis is stretched relative to the vertical axis.
+    ///
+    /// This is `1.0` for a uniform scale. Larger values fit more of the complex plane into each pixel horizontally,
+    /// squashing the set along the real axis. Keeping this as a ratio means zooming by changing `scale`
+    /// keeps the stretch intact.
+    #[cfg_attr(feature = "serde", serde(default = "default_stretch_x"))]
+    pub stretch_x: f32,
+}
+
+impl Viewport {
+    /// Creates a new `Viewport` given its center and scale.
+    #[inline]
+    pub const fn new(center: ComplexF32, scale: f32) -> Viewport {
+        Viewport { center, scale, stretch_x: 1.0 }
+    }
+
+    /// Creates a new `Viewport` with separate horizontal and vertical scales.
+    ///
+    /// `scale_y` is the half-height of the visible region, just like `scale` in [`Viewport::new`],
+    /// and `scale_x` is what it would be horizontally if the image were square.
+    #[inline]
+    pub fn with_scales(center: ComplexF32, scale_x: f32, scale_y: f32) -> Viewport {
+        Viewport { center, scale: scale_y, stretch_x: scale_x / scale_y }
+    }
+
+    /// The horizontal scale, see [`Viewport::with_scales`].
+    #[inline]
+    pub fn scale_x(&self) -> f32 {
+        self.scale * self.stretch_x
+    }
+
+    /// The vertical scale, which is the same as `scale`.
+    #[inline]
+    pub fn scale_y(&self) -> f32 {
+        self.scale
+    }
+
+    /// The width of the visible region in complex units, given the size of the image in pixels.
+    #[inline]
+    pub fn visible_width(&self, size: Point<u32>) -> f32 {
+        2.0 * self.scale_x() * size.x as f32 / size.y as f32
+    }
+
+    /// The height of the visible region in complex units.
+    #[inline]
+    pub fn visible_height(&self) -> f32 {
+        2.0 * self.scale
+    }
+
+    /// Converts a pixel coordinate to the complex number it represents, given the size of the image.
+    ///
+    /// The imaginary axis points up, so the top row of the image has the largest imaginary part.
+    #[inline]
+    pub fn pixel_to_complex(&self, p: Point<u32>, size: Point<u32>) -> ComplexF32 {
+        self.subpixel_to_complex(p.map(|v| v as f32), size)
+    }
+
+    /// Like [`Viewport::pixel_to_complex`], but for a point anywhere within the image, not just on whole pixels.
+    #[inline]
+    pub fn subpixel_to_complex(&self, p: Point<f32>, size: Point<u32>) -> ComplexF32 {
+        let half_height = size.y as f32 / 2.0;
+        let x = (p.x - size.x as f32 / 2.0) / half_height;
+        let y = (half_height - p.y) / half_height;
+
+        self.center + Complex::new(x * self.scale_x(), y * self.scale_y())
+    }
+
+    /// Converts a complex number back to the point of the image it falls on, the inverse of [`Viewport::subpixel_to_complex`].
+    ///
+    /// The result can lie outside of the image, or between pixels.
+    #[inline]
+    pub fn complex_to_subpixel(&self, c: ComplexF32, size: Point<u32>) -> Point<f32> {
+        let half_height = size.y as f32 / 2.0;
+        let d = c - self.center;
+
+        Point::new(
+            d.re / self.scale_x() * half_height + size.x as f32 / 2.0,
+            half_height - d.im / self.scale_y() * half_height,
+        )
+    }
+
+    /// Parses a location in the form fractal explorers share it in: the real and imaginary parts of the center
+    /// as decimal strings, and the zoom as a magnification factor, where a zoom of `1` shows a radius of `2`.
+    ///
+    /// The center is rounded to `f32`, which cannot hold the precision of deep zooms shared this way.
+    /// [`parse_explorer_params`] parses the same location in `f64`.
+    pub fn from_explorer_params(re: &str, im: &str, zoom: &str) -> Result<Viewport, ExplorerParamsError> {
+        let (center, scale) = parse_explorer_params(re, im, zoom)?;
+        Ok(Viewport::new(center.to_f32(), scale as f32))
+    }
+}
+
+impl Default for Viewport {
+    /// A view that fits the whole Mandelbrot set.
+    fn default() -> Self {
+        Viewport::new(Complex::new(-0.5, 0.0), 1.25)
+    }
+}
+
+/// The value of [`Viewport::stretch_x`] for viewports saved before it existed.
+#[inline]
+fn default_stretch_x() -> f32 {
+    1.0
+}
+
+/// An error that occurred while parsing a location shared by a fractal explorer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplorerParamsError {
+    /// One of the numbers was not a valid decimal number.
+    InvalidNumber(ParseFloatError),
+    /// The zoom was not a positive, finite magnification.
+    InvalidZoom,
+}
+
+impl fmt::Display for ExplorerParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExplorerParamsError::InvalidNumber(e) => write!(f, "invalid coordinate: {e}"),
+            ExplorerParamsError::InvalidZoom => write!(f, "zoom must be a positive, finite magnification"),
+        }
+    }
+}
+
+impl std::error::Error for ExplorerParamsError {}
+
+impl From<ParseFloatError> for ExplorerParamsError {
+    fn from(e: ParseFloatError) -> Self {
+        ExplorerParamsError::InvalidNumber(e)
+    }
+}
+
+/// Parses a location shared by a fractal explorer into its center and scale in `f64`,
+/// the form taken by [`render_perturbed`](crate::perturbation::render_perturbed).
+///
+/// See [`Viewport::from_explorer_params`] for the format. Surrounding whitespace is ignored.
+pub fn parse_explorer_params(re: &str, im: &str, zoom: &str) -> Result<(ComplexF64, f64), ExplorerParamsError> {
+    let center = Complex::new(re.trim().parse()?, im.trim().parse()?);
+    let zoom: f64 = zoom.trim().parse()?;
+
+    if !(zoom.is_finite() && zoom > 0.0) {
+        return Err(ExplorerParamsError::InvalidZoom);
+    }
+
+    Ok((center, EXPLORER_BASE_RADIUS / zoom))
+}
+
+/// Settings that control how escape counts are computed.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComputeSettings {
+    /// The maximum number of iterations before a point is considered to be inside the set.
+    pub max_iter: u32,
+    /// The norm used to decide when a point has escaped.
+    pub escape_norm: EscapeNorm,
+    /// An optional check that stops iterating points that are clearly inside the set.
+    pub early_out: Option<InteriorEarlyOut>,
+    /// Whether to compute the continuous escape value instead of the whole number of iterations.
+    ///
+    /// Smooth counts always use the Euclidean norm, since the smoothing formula assumes a round bailout.
+    pub smooth: bool,
+    /// Whether smooth counts look the nested logarithms of the smoothing formula up in a table instead of
+    /// computing them, which is faster but very slightly less accurate, see [`smooth_fraction_lut`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub smooth_lut: bool,
+    /// Whether [`compute_counts`] may mirror the lower half of the image from the upper half.
+    ///
+    /// The set is symmetric about the real axis, so when the viewport is centered on it, every row below the middle
+    /// is the mirror image of a row above it and only needs to be copied. Other viewports are computed as usual.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub use_symmetry: bool,
+}
+
+impl Default for ComputeSettings {
+    fn default() -> Self {
+        ComputeSettings {
+            max_iter: 256,
+            escape_norm: EscapeNorm::Euclidean,
+            early_out: None,
+            smooth: false,
+            smooth_lut: false,
+            use_symmetry: false,
+        }
+    }
+}
+
+impl ComputeSettings {
+    /// Computes the continuous escape value of an orbit that escaped at iteration `i` with the iterate `z`,
+    /// using the table from [`smooth_fraction_lut`] if `smooth_lut` is set.
+    #[inline]
+    pub fn smooth_fraction(&self, i: u32, z: ComplexF32) -> f32 {
+        if self.smooth_lut {
+            smooth_fraction_lut(i, z)
+        } else {
+            smooth_fraction(i, z)
+        }
+    }
+}
+
+/// Computes the escape count of a single point, or `None` if it is inside the set.
+#[inline]
+pub fn point_count(c: ComplexF32, settings: &ComputeSettings) -> Option<f32> {
+    if settings.smooth && settings.smooth_lut {
+        iterated_count(c, settings, |z, c| z * z + c)
+    } else if settings.smooth {
+        smooth_escape_time(c, settings.max_iter)
+    } else {
+        escape_time_with(c, settings.max_iter, settings.escape_norm, settings.early_out).map(|n| n as f32)
+    }
+}
+
+/// Computes the escape counts of the four points of a 2x2 block of pixels together, giving the same
+/// counts as calling [`point_count`] on each of them.
+///
+/// Neighboring pixels usually escape at about the same iteration, so iterating them in lockstep keeps the four
+/// lanes busy together, and the loop is written over arrays so that the compiler can turn it into SIMD. The block
+/// only stops once every lane has escaped or `max_iter` is reached. Settings with a non-Euclidean `escape_norm`
+/// or an `early_out` are not handled in lockstep, and fall back to iterating each point on its own.
+pub fn block_counts(c: [ComplexF32; 4], settings: &ComputeSettings) -> [Option<f32>; 4] {
+    let lockstep = settings.smooth || (settings.escape_norm == EscapeNorm::Euclidean && settings.early_out.is_none());
+    if !lockstep {
+        return c.map(|c| point_count(c, settings));
+    }
+
+    let c_re = c.map(|c| c.re);
+    let c_im = c.map(|c| c.im);
+    let mut re = [0.0f32; 4];
+    let mut im = [0.0f32; 4];
+    let mut counts = [None; 4];
+    let mut remaining = 4;
+
+    for i in 0..settings.max_iter {
+        for k in 0..4 {
+            // The same operations as `z * z + c`, so that every lane rounds exactly like `point_count`.
+            let next_re = re[k] * re[k] - im[k] * im[k] + c_re[k];
+            let next_im = re[k] * im[k] + im[k] * re[k] + c_im[k];
+            re[k] = next_re;
+            im[k] = next_im;
+        }
+
+        for k in 0..4 {
+            if counts[k].is_none() && re[k] * re[k] + im[k] * im[k] > BAILOUT_SQR {
+                let z = Complex::new(re[k], im[k]);
+                counts[k] = Some(if settings.smooth { settings.smooth_fraction(i, z) } else { i as f32 });
+                remaining -= 1;
+            }
+        }
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    counts
+}
+
+/// Like [`point_count`], but iterates `z = step(z, c)` instead of the Mandelbrot step, starting from `z = 0`.
+///
+/// `settings.early_out` relies on the derivative of the Mandelbrot step, so it is ignored here.
+/// Smooth counts use the same formula as the Mandelbrot set, which is only exact for steps of degree two.
+#[inline]
+pub fn iterated_count<F: Fn(ComplexF32, ComplexF32) -> ComplexF32>(c: ComplexF32, settings: &ComputeSettings, step: F) -> Option<f32> {
+    let norm = if settings.smooth { EscapeNorm::Euclidean } else { settings.escape_norm };
+    let mut z = Complex::new(0.0, 0.0);
+
+    for i in 0..settings.max_iter {
+        z = step(z, c);
+
+        if norm.is_escaped(z) {
+            return Some(if settings.smooth { settings.smooth_fraction(i, z) } else { i as f32 });
+        }
+    }
+
+    None
+}
+
+/// Whether the pixels `x_start..x_end` of row `y` are all known to be inside the set without iterating them.
+///
+/// This only recognizes rows that lie entirely within the main cardioid or the period-2 bulb,
+/// see [`segment_in_cardioid_or_bulb`]. Every other row has to be iterated as usual.
+#[inline]
+pub fn row_is_interior(viewport: &Viewport, size: Point<u32>, y: u32, x_start: u32, x_end: u32) -> bool {
+    x_start < x_end
+        && segment_in_cardioid_or_bulb(
+            viewport.pixel_to_complex(Point::new(x_start, y), size),
+            viewport.pixel_to_complex(Point::new(x_end - 1, y), size),
+        )
+}
+
+/// Computes the escape count of every pixel of an image of the given size.
+///
+/// Points inside the set have a count of `None`. Rows that lie entirely inside the set's largest
+/// components are filled in directly, see [`row_is_interior`], and with [`ComputeSettings::use_symmetry`]
+/// the lower half of a viewport centered on the real axis is mirrored from the upper half.
+pub fn compute_counts(size: Point<u32>, viewport: &Viewport, settings: &ComputeSettings) -> Field<Option<f32>> {
+    let mut field = Field { data: Vec::new(), size };
+    compute_into_field(&mut field, size, viewport, settings);
+    field
+}
+
+/// Like [`compute_counts`], but writes the counts into `field` instead of allocating a new one.
+///
+/// `field` is resized to `size`, and its buffer only has to grow when `size` holds more pixels than it has room for,
+/// so an interactive viewer that computes every frame into the same field does not allocate once it is warmed up.
+pub fn compute_into_field(field: &mut Field<Option<f32>>, size: Point<u32>, viewport: &Viewport, settings: &ComputeSettings) {
+    let counts = &mut field.data;
+    counts.clear();
+    counts.reserve_exact(size.x as usize * size.y as usize);
+    field.size = size;
+
+    let symmetric = settings.use_symmetry && viewport.center.im == 0.0;
+
+    for y in 0..size.y {
+        // Rows `y` and `size.y - y` lie at exactly opposite imaginary parts, and the top row has no partner.
+        let mirror = size.y - y;
+        if symmetric && mirror < y {
+            let start = mirror as usize * size.x as usize;
+            counts.extend_from_within(start..start + size.x as usize);
+            continue;
+        }
+
+        if row_is_interior(viewport, size, y, 0, size.x) {
+            counts.resize(counts.len() + size.x as usize, None);
+            continue;
+        }
+
+        for x in 0..size.x {
+            let c = viewport.pixel_to_complex(Point::new(x, y), size);
+            counts.push(point_count(c, settings));
+        }
+    }
+}
+
+/// Like [`compute_counts`], but packs the counts into a flat row-major buffer of `f32` with `NaN` for interior points.
+///
+/// This is the layout a GPU expects for a single channel float texture, so the buffer can be uploaded as is.
+/// The size of the image is returned alongside it.
+pub fn compute_field_f32(size: Point<u32>, viewport: &Viewport, settings: &ComputeSettings) -> (Vec<f32>, Point<u32>) {
+    let counts = compute_counts(size, viewport, settings);
+    let data = counts.data.into_iter().map(|count| count.unwrap_or(f32::NAN)).collect();
+
+    (data, counts.size)
+}
+
+/// Computes the escape counts of a frame of size `size` showing `next`, reusing the counts of the previous frame
+/// `prev`, which showed `prev_viewport`, wherever the two overlap.
+///
+/// A pixel whose point lands on the center of a pixel of the previous frame takes its count, and every other pixel
+/// is computed as usual. Panning by a whole number of pixels at the same scale therefore only computes the strip
+/// that was newly revealed. Zooming, or panning by fractions of a pixel, leaves nothing to reuse.
+///
+/// The reused counts were computed for points that can differ from the new ones by rounding, so a small fraction
+/// of pixels right on the boundary, where counts change quickly, can differ from what [`compute_counts`] would give.
+pub fn reproject_counts(
+    prev: &Field<Option<f32>>,
+    prev_viewport: &Viewport,
+    next: &Viewport,
+    size: Point<u32>,
+    settings: &ComputeSettings,
+) -> Field<Option<f32>> {
+    let mut counts = Vec::with_capacity(size.x as usize * size.y as usize);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let c = next.pixel_to_complex(Point::new(x, y), size);
+            let p = prev_viewport.complex_to_subpixel(c, prev.size);
+            let nearest = p.map(f32::round);
+
+            let reused = if (p.x - nearest.x).abs() < REPROJECT_TOLERANCE
+                && (p.y - nearest.y).abs() < REPROJECT_TOLERANCE
+                && nearest.x >= 0.0
+                && nearest.y >= 0.0
+            {
+                prev.get(nearest.map(|v| v as u32))
+            } else {
+                None
+            };
+
+            counts.push(match reused {
+                Some(&count) => count,
+                None => point_count(c, settings),
+            });
+        }
+    }
+
+    Field::new(counts, size)
+}
+
+/// Like [`compute_counts`], but gives the pixels inside the rectangle `roi` a higher iteration limit of `roi_max_iter`.
+///
+/// `roi` holds the top-left corner (inclusive) and bottom-right corner (exclusive) of the rectangle in pixels.
+/// This spends extra effort only where it is needed, such as around a detailed part of the boundary.
+pub fn render_with_roi(
+    size: Point<u32>,
+    viewport: &Viewport,
+    base_settings: &ComputeSettings,
+    roi: (Point<u32>, Point<u32>),
+    roi_max_iter: u32,
+) -> Field<Option<f32>> {
+    let roi_settings = ComputeSettings {
+        max_iter: roi_max_iter,
+        ..*base_settings
+    };
+
+    let (min, max) = roi;
+    let mut counts = Vec::with_capacity(size.x as usize * size.y as usize);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let in_roi = x >= min.x && x < max.x && y >= min.y && y < max.y;
+            let settings = if in_roi { &roi_settings } else { base_settings };
+
+            let c = viewport.pixel_to_complex(Point::new(x, y), size);
+            counts.push(point_count(c, settings));
+        }
+    }
+
+    Field::new(counts, size)
+}
+
+/// Looks up the complex number under the pixel `px` and its smooth escape value, or `None` if it is inside the set.
+///
+/// This is everything a viewer needs to show a tooltip for the point under the cursor.
+pub fn probe(viewport: &Viewport, size: Point<u32>, px: Point<u32>, max_iter: u32) -> (ComplexF32, Option<f32>) {
+    let c = viewport.pixel_to_complex(px, size);
+    (c, smooth_escape_time(c, max_iter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_size_follows_the_aspect_ratio() {
+        let viewport = Viewport::new(Complex::new(0.0, 0.0), 1.5);
+        let size = Point::new(1920, 1080);
+
+        assert_eq!(viewport.visible_height(), 3.0);
+        assert!((viewport.visible_width(size) - 3.0 * 16.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn separate_scales_stretch_one_axis() {
+        let size = Point::new(100, 100);
+        let uniform = Viewport::new(Complex::new(0.0, 0.0), 1.0);
+        let stretched = Viewport::with_scales(Complex::new(0.0, 0.0), 3.0, 1.0);
+
+        assert_eq!(stretched.scale_x(), 3.0);
+        assert_eq!(stretched.scale_y(), 1.0);
+
+        // The right edge lies three times as far out, while the top edge does not move.
+        let right = Point::new(100, 50);
+        assert_eq!(uniform.pixel_to_complex(right, size).re, 1.0);
+        assert_eq!(stretched.pixel_to_complex(right, size).re, 3.0);
+
+        let top = Point::new(50, 0);
+        assert_eq!(uniform.pixel_to_complex(top, size).im, stretched.pixel_to_complex(top, size).im);
+
+        let p = Point::new(17.5, 62.25);
+        let back = stretched.complex_to_subpixel(stretched.subpixel_to_complex(p, size), size);
+        assert!((back.x - p.x).abs() < 1e-3 && (back.y - p.y).abs() < 1e-3, "{back:?}");
+    }
+
+    #[test]
+    fn roi_pixels_get_the_higher_iteration_limit() {
+        let size = Point::new(40, 30);
+        let viewport = Viewport::default();
+        let base = ComputeSettings { max_iter: 20, ..ComputeSettings::default() };
+        let roi = (Point::new(10, 5), Point::new(30, 25));
+
+        let counts = render_with_roi(size, &viewport, &base, roi, 500);
+        let base_counts = compute_counts(size, &viewport, &base);
+        let mut newly_escaped = 0;
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let p = Point::new(x, y);
+                let (count, base_count) = (counts.get(p).unwrap(), base_counts.get(p).unwrap());
+                let in_roi = (10..30).contains(&x) && (5..25).contains(&y);
+
+                match (in_roi, base_count) {
+                    (false, _) | (true, Some(_)) => assert_eq!(count, base_count, "at {p:?}"),
+                    (true, None) => newly_escaped += count.is_some_and(|count| count >= 20.0) as u32,
+                }
+            }
+        }
+
+        assert!(newly_escaped > 0);
+    }
+
+    #[test]
+    fn probing_the_image_center_gives_the_viewport_center() {
+        let viewport = Viewport::new(Complex::new(-0.75, 0.1), 0.05);
+        let size = Point::new(200, 100);
+
+        let (c, count) = probe(&viewport, size, Point::new(100, 50), 1000);
+        assert_eq!(c.canonical_bits(), viewport.center.canonical_bits());
+        assert_eq!(count, smooth_escape_time(viewport.center, 1000));
+
+        let (_, count) = probe(&Viewport::new(Complex::new(-0.1, 0.0), 0.1), size, Point::new(100, 50), 1000);
+        assert_eq!(count, None);
+    }
+
+    #[test]
+    fn interior_rows_match_brute_force() {
+        let settings = ComputeSettings::default();
+        let size = Point::new(24, 16);
+
+        for viewport in [Viewport::new(Complex::new(-0.1, 0.0), 0.05), Viewport::new(Complex::new(-1.0, 0.0), 0.1), Viewport::default()] {
+            let brute_force: Vec<_> = (0..size.y)
+                .flat_map(|y| (0..size.x).map(move |x| Point::new(x, y)))
+                .map(|p| point_count(viewport.pixel_to_complex(p, size), &settings))
+                .collect();
+
+            assert_eq!(compute_counts(size, &viewport, &settings).data, brute_force, "{viewport:?}");
+        }
+
+        assert!(row_is_interior(&Viewport::new(Complex::new(-0.1, 0.0), 0.05), size, 8, 0, size.x));
+        assert!(!row_is_interior(&Viewport::default(), size, 8, 0, size.x));
+    }
+
+    #[test]
+    fn parses_a_deep_zoom_location() {
+        let re = " -0.743643887037158704752191506114774 ";
+        let im = "0.131825904205311970493132056385139";
+        let (center, scale) = parse_explorer_params(re, im, "2.5e12").unwrap();
+
+        assert_eq!(center.re, -0.7436438870371587);
+        assert_eq!(center.im, 0.13182590420531197);
+        assert!((scale - 8e-13).abs() < 1e-27, "{scale}");
+
+        let viewport = Viewport::from_explorer_params(re, im, "2.5e12").unwrap();
+        assert_eq!(viewport.center.re, center.re as f32);
+        assert_eq!(viewport.scale, 8e-13);
+
+        assert!(matches!(parse_explorer_params(re, im, "0"), Err(ExplorerParamsError::InvalidZoom)));
+        assert!(matches!(parse_explorer_params("-0.74.3", im, "1"), Err(ExplorerParamsError::InvalidNumber(_))));
+    }
+
+    #[test]
+    fn panning_recomputes_only_the_revealed_strip() {
+        // Pixels are exactly 1/32 wide, so panning by 5/32 moves the image by exactly 5 pixels.
+        let size = Point::new(48, 64);
+        let settings = ComputeSettings { max_iter: 100, ..ComputeSettings::default() };
+        let prev_viewport = Viewport::new(Complex::new(-0.5, 0.0), 1.0);
+        let next = Viewport::new(Complex::new(-0.5 + 5.0 / 32.0, 0.0), 1.0);
+
+        // A marker count that no pixel can really have, to see which pixels were reused.
+        let marked = Field::new(vec![Some(-1.0); size.x as usize * size.y as usize], size);
+        let reprojected = reproject_counts(&marked, &prev_viewport, &next, size, &settings);
+        let full = compute_counts(size, &next, &settings);
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let p = Point::new(x, y);
+                let count = reprojected.get(p).unwrap();
+
+                if x < size.x - 5 {
+                    assert_eq!(*count, Some(-1.0), "{p:?} should have been reused");
+                } else {
+                    assert_eq!(count, full.get(p).unwrap(), "{p:?} should have been computed");
+                }
+            }
+        }
+
+        let prev = compute_counts(size, &prev_viewport, &settings);
+        assert_eq!(reproject_counts(&prev, &prev_viewport, &next, size, &settings), full);
+    }
+
+    #[test]
+    fn float_field_packs_interior_as_nan() {
+        let size = Point::new(37, 21);
+        let viewport = Viewport::default();
+        let settings = ComputeSettings { max_iter: 100, ..ComputeSettings::default() };
+
+        let (data, data_size) = compute_field_f32(size, &viewport, &settings);
+        assert_eq!(data_size, size);
+        assert_eq!(data.len(), 37 * 21);
+
+        let counts = compute_counts(size, &viewport, &settings);
+        for (value, count) in data.iter().zip(&counts.data) {
+            match count {
+                Some(count) => assert!(value.is_finite() && value.to_bits() == count.to_bits(), "{value} for {count}"),
+                None => assert!(value.is_nan()),
+            }
+        }
+
+        // The middle of the image lies in the main cardioid, and the corners lie far outside the set.
+        assert!(data[10 * 37 + 18].is_nan());
+        assert!(data[0].is_finite() && data[data.len() - 1].is_finite());
+    }
+
+    #[test]
+    fn symmetry_does_not_change_the_counts() {
+        let settings = ComputeSettings { max_iter: 200, ..ComputeSettings::default() };
+        let mirrored = ComputeSettings { use_symmetry: true, ..settings };
+
+        for size in [Point::new(30, 20), Point::new(30, 21)] {
+            for viewport in [Viewport::default(), Viewport::new(Complex::new(-0.75, 0.0), 0.1), Viewport::new(Complex::new(-0.75, 0.05), 0.1)] {
+                let expected = compute_counts(size, &viewport, &settings);
+                assert_eq!(compute_counts(size, &viewport, &mirrored), expected, "{viewport:?} at {size:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn reused_field_keeps_its_buffer() {
+        let settings = ComputeSettings { max_iter: 100, ..ComputeSettings::default() };
+        let mut field = Field { data: Vec::new(), size: Point::new(0, 0) };
+
+        compute_into_field(&mut field, Point::new(40, 30), &Viewport::default(), &settings);
+        let (capacity, buffer) = (field.data.capacity(), field.data.as_ptr());
+
+        for (size, viewport) in [
+            (Point::new(21, 13), Viewport::new(Complex::new(-0.75, 0.1), 0.2)),
+            (Point::new(40, 30), Viewport::new(Complex::new(0.3, -0.5), 0.05)),
+        ] {
+            compute_into_field(&mut field, size, &viewport, &settings);
+            assert_eq!((field.data.capacity(), field.data.as_ptr()), (capacity, buffer));
+            assert_eq!(field, compute_counts(size, &viewport, &settings), "{viewport:?} at {size:?}");
+        }
+    }
+}
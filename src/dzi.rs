@@ -0,0 +1,113 @@
+#![allow(unused)]
+
+use std::fs;
+use std::path::Path;
+
+use image::error::{ImageError, ParameterError, ParameterErrorKind};
+use image::ImageResult;
+
+use crate::compute::Viewport;
+use crate::palette::Palette;
+use crate::render::{render_tile, RenderSettings};
+use crate::util::Point;
+
+/// The name of the descriptor file and tile directory written by [`export_dzi`], as `<name>.dzi` and `<name>_files`.
+const DZI_NAME: &str = "mandelbrot";
+
+/// Renders a Deep Zoom (DZI) tile pyramid of `base_viewport` into `out_dir`, for web viewers like OpenSeadragon.
+///
+/// The full image is `2^max_level` pixels square, and each level below it halves the size down to a single pixel
+/// at level `0`. Every level is rendered directly at its own resolution rather than downsampled from the one above,
+/// and split into `tile_size` square tiles, with those along the right and bottom edges cut short.
+///
+/// The tiles are saved as `mandelbrot_files/<level>/<column>_<row>.png`, next to the `mandelbrot.dzi` descriptor.
+/// A `max_level` above `31`, whose full size does not fit in a `u32`, or a `tile_size` of `0` is rejected
+/// with a parameter error before anything is written.
+pub fn export_dzi(
+    base_viewport: &Viewport,
+    max_level: u32,
+    tile_size: u32,
+    settings: &RenderSettings,
+    palette: &Palette,
+    out_dir: &str,
+) -> ImageResult<()> {
+    let invalid = |message: &str| Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::Generic(message.to_owned()))));
+    if max_level >= u32::BITS {
+        return invalid("max level must be at most 31");
+    }
+    if tile_size == 0 {
+        return invalid("tile size must be at least 1");
+    }
+
+    let out_dir = Path::new(out_dir);
+    let tiles_dir = out_dir.join(format!("{DZI_NAME}_files"));
+    let full_size = 1u32 << max_level;
+
+    for level in 0..=max_level {
+        let size = 1u32 << level;
+        let level_dir = tiles_dir.join(level.to_string());
+        fs::create_dir_all(&level_dir)?;
+
+        for row in 0..size.div_ceil(tile_size) {
+            for column in 0..size.div_ceil(tile_size) {
+                let origin = Point::new(column * tile_size, row * tile_size);
+                let tile_dims = Point::new(tile_size.min(size - origin.x), tile_size.min(size - origin.y));
+                let total_size = Point::new(size, size);
+
+                let tile = render_tile(origin, tile_dims, total_size, base_viewport, settings, palette);
+                tile.save(level_dir.join(format!("{column}_{row}.png")))?;
+            }
+        }
+    }
+
+    let descriptor = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Image xmlns="http://schemas.microsoft.com/deepzoom/2008" TileSize="{tile_size}" Overlap="0" Format="png">
+    <Size Width="{full_size}" Height="{full_size}"/>
+</Image>
+"#
+    );
+    fs::write(out_dir.join(format!("{DZI_NAME}.dzi")), descriptor)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_every_tile_and_the_descriptor() {
+        let out_dir = std::env::temp_dir().join("mandelbrot_test_dzi");
+        let _ = fs::remove_dir_all(&out_dir);
+        export_dzi(&Viewport::default(), 4, 6, &RenderSettings::default(), &Palette::grayscale(), out_dir.to_str().unwrap()).unwrap();
+
+        // Levels 0 to 4 are 1, 2, 4, 8 and 16 pixels square, split into tiles of 6.
+        for (level, tiles_per_side, last_tile) in [(0, 1, 1), (1, 1, 2), (2, 1, 4), (3, 2, 2), (4, 3, 4)] {
+            let level_dir = out_dir.join(format!("{DZI_NAME}_files/{level}"));
+            assert_eq!(fs::read_dir(&level_dir).unwrap().count(), tiles_per_side * tiles_per_side, "level {level}");
+
+            let last = tiles_per_side - 1;
+            let corner = image::open(level_dir.join(format!("{last}_{last}.png"))).unwrap();
+            assert_eq!((corner.width(), corner.height()), (last_tile, last_tile), "level {level}");
+        }
+
+        let descriptor = fs::read_to_string(out_dir.join(format!("{DZI_NAME}.dzi"))).unwrap();
+        assert!(descriptor.contains(r#"TileSize="6" Overlap="0" Format="png""#), "{descriptor}");
+        assert!(descriptor.contains(r#"<Size Width="16" Height="16"/>"#), "{descriptor}");
+
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let out_dir = std::env::temp_dir().join("mandelbrot_test_dzi_invalid");
+        let export = |max_level, tile_size| {
+            export_dzi(&Viewport::default(), max_level, tile_size, &RenderSettings::default(), &Palette::grayscale(), out_dir.to_str().unwrap())
+        };
+
+        assert!(matches!(export(32, 256), Err(ImageError::Parameter(_))));
+        assert!(matches!(export(2, 0), Err(ImageError::Parameter(_))));
+        assert!(!out_dir.exists());
+    }
+}
@@ -0,0 +1,93 @@
+#![allow(unused)]
+
+use crate::math::Point;
+
+/// A 2D grid of values, one per pixel, stored in row-major order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<T> {
+    pub data: Vec<T>,
+    pub size: Point<u32>,
+}
+
+impl<T> Field<T> {
+    /// Creates a new `Field` from its row-major data and size.
+    ///
+    /// Panics if the length of `data` does not match `size`.
+    pub fn new(data: Vec<T>, size: Point<u32>) -> Field<T> {
+        assert_eq!(data.len(), size.x as usize * size.y as usize, "field data does not match its size");
+        Field { data, size }
+    }
+
+    /// The index into `data` of the pixel `p`.
+    #[inline]
+    pub fn index(&self, p: Point<u32>) -> usize {
+        p.y as usize * self.size.x as usize + p.x as usize
+    }
+
+    /// The value at the pixel `p`, or `None` if it lies outside of the field.
+    #[inline]
+    pub fn get(&self, p: Point<u32>) -> Option<&T> {
+        if p.x < self.size.x && p.y < self.size.y {
+            Some(&self.data[self.index(p)])
+        } else {
+            None
+        }
+    }
+
+    /// The value at the pixel `p`, with coordinates outside of the field clamped to its nearest edge.
+    #[inline]
+    pub fn get_clamped(&self, p: Point<i64>) -> &T {
+        let x = p.x.clamp(0, self.size.x as i64 - 1) as u32;
+        let y = p.y.clamp(0, self.size.y as i64 - 1) as u32;
+
+        &self.data[self.index(Point::new(x, y))]
+    }
+
+    /// The values to the left, right, top and bottom of the pixel `p`, in that order.
+    ///
+    /// Neighbors past the edge of the field are clamped, so pixels along the edge are their own neighbor.
+    #[inline]
+    pub fn neighbors(&self, p: Point<u32>) -> [&T; 4] {
+        let x = p.x as i64;
+        let y = p.y as i64;
+
+        [
+            self.get_clamped(Point::new(x - 1, y)),
+            self.get_clamped(Point::new(x + 1, y)),
+            self.get_clamped(Point::new(x, y - 1)),
+            self.get_clamped(Point::new(x, y + 1)),
+        ]
+    }
+
+    /// Maps a function over every value of this field.
+    #[inline]
+    pub fn map<F: Fn(&T) -> U, U>(&self, f: F) -> Field<U> {
+        Field {
+            data: self.data.iter().map(f).collect(),
+            size: self.size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_are_clamped_at_the_corners() {
+        // 0 1 2
+        // 3 4 5
+        let field = Field::new((0..6).collect(), Point::new(3, 2));
+
+        assert_eq!(*field.get_clamped(Point::new(-5, -5)), 0);
+        assert_eq!(*field.get_clamped(Point::new(10, -1)), 2);
+        assert_eq!(*field.get_clamped(Point::new(-1, 7)), 3);
+        assert_eq!(*field.get_clamped(Point::new(3, 2)), 5);
+        assert_eq!(*field.get_clamped(Point::new(1, 1)), 4);
+        assert_eq!(field.get(Point::new(3, 0)), None);
+
+        assert_eq!(field.neighbors(Point::new(0, 0)), [&0, &1, &0, &3]);
+        assert_eq!(field.neighbors(Point::new(2, 1)), [&4, &5, &2, &5]);
+        assert_eq!(field.neighbors(Point::new(1, 0)), [&0, &2, &1, &4]);
+    }
+}
@@ -0,0 +1,48 @@
+#![allow(unused)]
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::TiffResult;
+
+use crate::field::Field;
+
+/// Saves a field of escape counts as a single-channel 32-bit float TIFF, for post-processing outside of this crate.
+///
+/// The counts are stored exactly as they were computed, with points inside the set stored as `NaN`.
+pub fn save_float_tiff(counts: &Field<Option<f32>>, path: &str) -> TiffResult<()> {
+    let data: Vec<f32> = counts.data.iter().map(|count| count.unwrap_or(f32::NAN)).collect();
+
+    let mut encoder = TiffEncoder::new(BufWriter::new(File::create(path)?))?;
+    encoder.write_image::<colortype::Gray32Float>(counts.size.x, counts.size.y, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::BufReader;
+
+    use tiff::decoder::{Decoder, DecodingResult};
+
+    use crate::util::Point;
+
+    #[test]
+    fn counts_survive_the_round_trip() {
+        let counts = Field::new(vec![Some(0.0), Some(1.5), None, Some(123.456_79), Some(f32::MIN_POSITIVE), None], Point::new(3, 2));
+        let path = std::env::temp_dir().join("mandelbrot_test_float.tiff");
+        save_float_tiff(&counts, path.to_str().unwrap()).unwrap();
+
+        let mut decoder = Decoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let dimensions = decoder.dimensions().unwrap();
+        let image = decoder.read_image().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(dimensions, (3, 2));
+        let DecodingResult::F32(data) = image else { panic!("not a float image: {image:?}") };
+        let read: Vec<Option<u32>> = data.iter().map(|v| (!v.is_nan()).then(|| v.to_bits())).collect();
+        let expected: Vec<Option<u32>> = counts.data.iter().map(|count| count.map(f32::to_bits)).collect();
+        assert_eq!(read, expected);
+    }
+}
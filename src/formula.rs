@@ -0,0 +1,318 @@
+#![allow(unused)]
+
+use std::fmt;
+use std::num::ParseFloatError;
+
+use crate::math::{Complex, ComplexF32};
+
+/// An error that occurred while parsing an iteration formula, see [`parse_formula`].
+///
+/// Positions are byte offsets into the formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormulaError {
+    /// A character that is not part of any token.
+    UnexpectedChar(usize),
+    /// A token that cannot appear where it does.
+    UnexpectedToken(usize),
+    /// The formula ended in the middle of an expression.
+    UnexpectedEnd,
+    /// A name that is neither a variable nor a known function.
+    UnknownName(String),
+    /// A number that could not be parsed.
+    InvalidNumber(ParseFloatError),
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaError::UnexpectedChar(pos) => write!(f, "unexpected character at position {pos}"),
+            FormulaError::UnexpectedToken(pos) => write!(f, "unexpected token at position {pos}"),
+            FormulaError::UnexpectedEnd => write!(f, "unexpected end of formula"),
+            FormulaError::UnknownName(name) => write!(f, "unknown name `{name}`"),
+            FormulaError::InvalidNumber(e) => write!(f, "invalid number: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+impl From<ParseFloatError> for FormulaError {
+    fn from(e: ParseFloatError) -> Self {
+        FormulaError::InvalidNumber(e)
+    }
+}
+
+/// Parses an iteration formula such as `"z*z + c"` into a step that can be passed to
+/// [`render_iterated`](crate::render::render_iterated).
+///
+/// Formulas are made of the variables `z` and `c`, numbers such as `2`, `0.5` and `1e-3`, imaginary numbers
+/// such as `i` and `0.3i`, the operators `+ - * /` with the usual precedence, parentheses, and the functions
+/// `sin`, `cos`, `exp` and `conj`. There is no implicit multiplication, so `2z` has to be written as `2*z`.
+///
+/// The formula is walked as a tree for every step, which is a lot slower than a closure written out in code,
+/// but lets formulas be tried out without recompiling.
+pub fn parse_formula(src: &str) -> Result<impl Fn(ComplexF32, ComplexF32) -> ComplexF32, FormulaError> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser { tokens: &tokens, next: 0 };
+    let expr = parser.expr()?;
+
+    if let Some(&(_, pos)) = parser.tokens.get(parser.next) {
+        return Err(FormulaError::UnexpectedToken(pos));
+    }
+
+    Ok(move |z, c| expr.eval(z, c))
+}
+
+/// A parsed formula.
+#[derive(Debug, Clone)]
+enum Expr {
+    Z,
+    C,
+    Literal(ComplexF32),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+/// The functions that can be called in a formula.
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Sin,
+    Cos,
+    Exp,
+    Conj,
+}
+
+impl Expr {
+    /// Evaluates this formula for the given values of `z` and `c`.
+    fn eval(&self, z: ComplexF32, c: ComplexF32) -> ComplexF32 {
+        match self {
+            Expr::Z => z,
+            Expr::C => c,
+            Expr::Literal(v) => *v,
+            Expr::Neg(a) => Complex::new(0.0, 0.0) - a.eval(z, c),
+            Expr::Add(a, b) => a.eval(z, c) + b.eval(z, c),
+            Expr::Sub(a, b) => a.eval(z, c) - b.eval(z, c),
+            Expr::Mul(a, b) => a.eval(z, c) * b.eval(z, c),
+            Expr::Div(a, b) => a.eval(z, c) / b.eval(z, c),
+            Expr::Call(f, a) => {
+                let v = a.eval(z, c);
+                match f {
+                    Function::Sin => v.sin(),
+                    Function::Cos => v.cos(),
+                    Function::Exp => v.exp(),
+                    Function::Conj => v.reflect_real(),
+                }
+            }
+        }
+    }
+}
+
+/// A piece of a formula.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Imaginary(f32),
+    Name(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+/// Splits a formula into tokens, each paired with its position.
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, FormulaError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+
+    while let Some(&(pos, ch)) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let mut end = pos;
+            let mut prev = ch;
+            // An exponent may carry its own sign, as in `1e-3`.
+            while let Some(&(i, ch)) = chars.peek() {
+                if ch.is_ascii_digit() || ch == '.' || ch == 'e' || ((ch == '-' || ch == '+') && prev == 'e') {
+                    end = i + ch.len_utf8();
+                    prev = ch;
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+
+            let value = src[pos..end].parse()?;
+            let is_name_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+            let imaginary = src[end..].starts_with('i') && !src[end + 1..].starts_with(is_name_char);
+
+            if imaginary {
+                chars.next();
+                tokens.push((Token::Imaginary(value), pos));
+            } else {
+                tokens.push((Token::Number(value), pos));
+            }
+        } else if ch.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if ch.is_ascii_alphanumeric() || ch == '_' {
+                    name.push(ch);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+
+            tokens.push((Token::Name(name), pos));
+        } else {
+            let token = match ch {
+                '+' | '-' | '*' | '/' => Token::Op(ch),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return Err(FormulaError::UnexpectedChar(pos)),
+            };
+
+            chars.next();
+            tokens.push((token, pos));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of a formula.
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    next: usize,
+}
+
+impl Parser<'_> {
+    /// Takes the next token if it is the operator `op`.
+    fn eat_op(&mut self, op: char) -> bool {
+        let matches = matches!(self.tokens.get(self.next), Some((Token::Op(o), _)) if *o == op);
+        if matches {
+            self.next += 1;
+        }
+
+        matches
+    }
+
+    /// Parses a sum or difference of terms.
+    fn expr(&mut self) -> Result<Expr, FormulaError> {
+        let mut lhs = self.term()?;
+
+        loop {
+            if self.eat_op('+') {
+                lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+            } else if self.eat_op('-') {
+                lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    /// Parses a product or quotient of factors.
+    fn term(&mut self) -> Result<Expr, FormulaError> {
+        let mut lhs = self.unary()?;
+
+        loop {
+            if self.eat_op('*') {
+                lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat_op('/') {
+                lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    /// Parses a factor with any number of leading signs.
+    fn unary(&mut self) -> Result<Expr, FormulaError> {
+        if self.eat_op('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat_op('+') {
+            self.unary()
+        } else {
+            self.atom()
+        }
+    }
+
+    /// Parses a number, variable, function call or parenthesized expression.
+    fn atom(&mut self) -> Result<Expr, FormulaError> {
+        let (token, pos) = self.tokens.get(self.next).ok_or(FormulaError::UnexpectedEnd)?;
+        self.next += 1;
+
+        match token {
+            Token::Number(v) => Ok(Expr::Literal(Complex::new(*v, 0.0))),
+            Token::Imaginary(v) => Ok(Expr::Literal(Complex::new(0.0, *v))),
+            Token::Open => {
+                let inner = self.expr()?;
+                self.close()?;
+                Ok(inner)
+            }
+            Token::Name(name) => match name.as_str() {
+                "z" => Ok(Expr::Z),
+                "c" => Ok(Expr::C),
+                "i" => Ok(Expr::Literal(Complex::new(0.0, 1.0))),
+                _ => {
+                    let function = match name.as_str() {
+                        "sin" => Function::Sin,
+                        "cos" => Function::Cos,
+                        "exp" => Function::Exp,
+                        "conj" => Function::Conj,
+                        _ => return Err(FormulaError::UnknownName(name.clone())),
+                    };
+
+                    match self.tokens.get(self.next) {
+                        Some((Token::Open, _)) => self.next += 1,
+                        Some(&(_, pos)) => return Err(FormulaError::UnexpectedToken(pos)),
+                        None => return Err(FormulaError::UnexpectedEnd),
+                    }
+
+                    let arg = self.expr()?;
+                    self.close()?;
+                    Ok(Expr::Call(function, Box::new(arg)))
+                }
+            },
+            Token::Op(_) | Token::Close => Err(FormulaError::UnexpectedToken(*pos)),
+        }
+    }
+
+    /// Takes the closing parenthesis of a group or function call.
+    fn close(&mut self) -> Result<(), FormulaError> {
+        match self.tokens.get(self.next) {
+            Some((Token::Close, _)) => {
+                self.next += 1;
+                Ok(())
+            }
+            Some(&(_, pos)) => Err(FormulaError::UnexpectedToken(pos)),
+            None => Err(FormulaError::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mandelbrot_formula_matches_the_step() {
+        let step = parse_formula("z*z + c").unwrap();
+
+        for (z, c) in [((0.0, 0.0), (-0.75, 0.1)), ((0.3, -1.2), (0.25, 0.0)), ((-1.5, 0.5), (-2.0, 1.0))] {
+            let (z, c) = (Complex::new(z.0, z.1), Complex::new(c.0, c.1));
+            assert_eq!(step(z, c).canonical_bits(), (z * z + c).canonical_bits(), "{z:?} and {c:?}");
+        }
+    }
+
+    #[test]
+    fn malformed_formulas_are_rejected() {
+        assert!(matches!(parse_formula("z*z +"), Err(FormulaError::UnexpectedEnd)));
+        assert!(matches!(parse_formula("z $ c"), Err(FormulaError::UnexpectedChar(2))));
+        assert!(matches!(parse_formula("tan(z)"), Err(FormulaError::UnknownName(name)) if name == "tan"));
+        assert!(matches!(parse_formula("z c"), Err(FormulaError::UnexpectedToken(2))));
+    }
+}
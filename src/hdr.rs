@@ -0,0 +1,55 @@
+#![allow(unused)]
+
+use crate::compute::Viewport;
+use crate::field::Field;
+use crate::palette::Palette;
+use crate::render::{colorize, RenderSettings};
+
+/// Colors a field of escape counts and saves it as a 32-bit float OpenEXR image.
+///
+/// Unlike saving a `MandelbrotImage`, colors are not clamped to `[0, 1]`, so `palette_hdr` can contain
+/// colors brighter than white and compositors will still be able to tell highlights apart.
+pub fn save_exr(
+    counts: &Field<Option<f32>>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette_hdr: &Palette,
+    path: &str,
+) -> exr::error::UnitResult {
+    let colors = colorize(counts, viewport, settings, palette_hdr);
+    let size = colors.size;
+
+    exr::prelude::write_rgb_file(path, size.x as usize, size.y as usize, |x, y| {
+        let c = colors.data[y * size.x as usize + x];
+        (c.r, c.g, c.b)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use exr::prelude::*;
+
+    use crate::util::{Color, Point};
+
+    #[test]
+    fn colors_brighter_than_white_survive_the_round_trip() {
+        let counts = Field::new(vec![Some(5.0), None], Point::new(2, 1));
+        let palette = Palette::new(vec![Color::new(4.0, 2.5, 0.25)]);
+        let path = std::env::temp_dir().join("mandelbrot_test_hdr.exr");
+        save_exr(&counts, &Viewport::default(), &RenderSettings::default(), &palette, path.to_str().unwrap()).unwrap();
+
+        let image = read_first_rgba_layer_from_file(
+            &path,
+            |resolution, _| vec![(0.0, 0.0, 0.0); resolution.width() * resolution.height()],
+            |pixels: &mut Vec<(f32, f32, f32)>, position, (r, g, b, _): (f32, f32, f32, f32)| {
+                pixels[position.y() * 2 + position.x()] = (r, g, b);
+            },
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.layer_data.channel_data.pixels, vec![(4.0, 2.5, 0.25), (0.0, 0.0, 0.0)]);
+    }
+}
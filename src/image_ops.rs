@@ -0,0 +1,282 @@
+#![allow(unused)]
+
+use std::f32::consts::PI;
+
+use image::Rgb;
+
+use crate::util::{Color, MandelbrotImage, Point};
+
+/// The number of bytes that make up a single pixel of a `MandelbrotImage`.
+const BYTES_PER_PIXEL: usize = 3;
+
+/// Mirrors an image across its vertical center line, swapping its left and right sides.
+pub fn flip_horizontal(im: &mut MandelbrotImage) {
+    let width = im.width() as usize;
+    let row_len = width * BYTES_PER_PIXEL;
+    let data: &mut [u8] = im;
+
+    for row in data.chunks_exact_mut(row_len) {
+        for x in 0..width / 2 {
+            let a = x * BYTES_PER_PIXEL;
+            let b = (width - 1 - x) * BYTES_PER_PIXEL;
+
+            for i in 0..BYTES_PER_PIXEL {
+                row.swap(a + i, b + i);
+            }
+        }
+    }
+}
+
+/// Mirrors an image across its horizontal center line, swapping its top and bottom sides.
+pub fn flip_vertical(im: &mut MandelbrotImage) {
+    let height = im.height() as usize;
+    let row_len = im.width() as usize * BYTES_PER_PIXEL;
+    let data: &mut [u8] = im;
+
+    for y in 0..height / 2 {
+        let (top, bottom) = data.split_at_mut((height - 1 - y) * row_len);
+        top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+    }
+}
+
+/// Rotates an image by 180 degrees.
+pub fn rotate_180(im: &mut MandelbrotImage) {
+    let data: &mut [u8] = im;
+    let len = data.len() / BYTES_PER_PIXEL;
+
+    for p in 0..len / 2 {
+        let a = p * BYTES_PER_PIXEL;
+        let b = (len - 1 - p) * BYTES_PER_PIXEL;
+
+        for i in 0..BYTES_PER_PIXEL {
+            data.swap(a + i, b + i);
+        }
+    }
+}
+
+/// Copies `top` onto `base` with its top-left corner at the pixel `at`.
+///
+/// Any part of `top` that would fall outside of `base` is clipped, so an inset can hang over the edge.
+pub fn overlay(base: &mut MandelbrotImage, top: &MandelbrotImage, at: Point<u32>) {
+    if at.x >= base.width() || at.y >= base.height() {
+        return;
+    }
+
+    let width = top.width().min(base.width() - at.x) as usize;
+    let height = top.height().min(base.height() - at.y) as usize;
+    let base_row_len = base.width() as usize * BYTES_PER_PIXEL;
+    let top_row_len = top.width() as usize * BYTES_PER_PIXEL;
+    let copy_len = width * BYTES_PER_PIXEL;
+    let data: &mut [u8] = base;
+    let top: &[u8] = top;
+
+    for y in 0..height {
+        let start = (at.y as usize + y) * base_row_len + at.x as usize * BYTES_PER_PIXEL;
+        let row = &top[y * top_row_len..y * top_row_len + copy_len];
+        data[start..start + copy_len].copy_from_slice(row);
+    }
+}
+
+/// The filter used to resample an image in [`resize`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Copies the nearest pixel, which keeps edges hard and colors exact.
+    Nearest,
+    /// Interpolates linearly between the nearest pixels.
+    Bilinear,
+    /// Weights the pixels within three pixels with a windowed sinc, which keeps fine detail sharp.
+    #[default]
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    /// How far the filter reaches, in source pixels, when the image is not being shrunk.
+    fn support(self) -> f32 {
+        match self {
+            ResizeFilter::Nearest => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// The weight of a pixel at a distance of `x` source pixels.
+    fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+
+        match self {
+            ResizeFilter::Nearest => 1.0,
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::Lanczos3 if x < 1e-6 => 1.0,
+            ResizeFilter::Lanczos3 if x < 3.0 => {
+                let px = PI * x;
+                3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+            }
+            ResizeFilter::Lanczos3 => 0.0,
+        }
+    }
+
+    /// The source pixels and their normalized weights that make up each of the `dst_len` pixels along one axis.
+    ///
+    /// When shrinking, the filter is stretched to cover every source pixel, so detail is averaged instead of aliased.
+    fn contributions(self, src_len: u32, dst_len: u32) -> Vec<Vec<(usize, f32)>> {
+        let scale = src_len as f32 / dst_len as f32;
+        let filter_scale = scale.max(1.0);
+        let support = self.support() * filter_scale;
+
+        (0..dst_len)
+            .map(|i| {
+                let center = (i as f32 + 0.5) * scale - 0.5;
+                let start = (center - support).ceil() as i64;
+                let end = (center + support).floor() as i64;
+
+                let mut weights: Vec<(usize, f32)> = (start..=end)
+                    .map(|j| {
+                        let w = self.weight((j as f32 - center) / filter_scale);
+                        (j.clamp(0, src_len as i64 - 1) as usize, w)
+                    })
+                    .filter(|&(_, w)| w != 0.0)
+                    .collect();
+
+                let total: f32 = weights.iter().map(|&(_, w)| w).sum();
+                for (_, w) in &mut weights {
+                    *w /= total;
+                }
+
+                weights
+            })
+            .collect()
+    }
+}
+
+/// Resamples an image to `new_size` using `filter`.
+///
+/// Filtering is done on linear RGB rather than the stored sRGB values, so averaging a fine pattern of
+/// bright and dark pixels keeps its overall brightness. Pixels past the edges repeat the edge pixels.
+pub fn resize(im: &MandelbrotImage, new_size: Point<u32>, filter: ResizeFilter) -> MandelbrotImage {
+    let (src_w, src_h) = im.dimensions();
+
+    if filter == ResizeFilter::Nearest {
+        return MandelbrotImage::from_fn(new_size.x, new_size.y, |x, y| {
+            let sx = ((x as u64 * src_w as u64) / new_size.x as u64) as u32;
+            let sy = ((y as u64 * src_h as u64) / new_size.y as u64) as u32;
+            *im.get_pixel(sx, sy)
+        });
+    }
+
+    let linear: Vec<Color> = im.pixels().map(|&p| Color::from(p).to_linear()).collect();
+    let columns = filter.contributions(src_w, new_size.x);
+    let rows = filter.contributions(src_h, new_size.y);
+
+    let mut horizontal = Vec::with_capacity(new_size.x as usize * src_h as usize);
+    for y in 0..src_h as usize {
+        let row = &linear[y * src_w as usize..(y + 1) * src_w as usize];
+
+        for weights in &columns {
+            horizontal.push(weights.iter().fold(Color::BLACK, |acc, &(x, w)| acc + row[x] * w));
+        }
+    }
+
+    let mut out = MandelbrotImage::new(new_size.x, new_size.y);
+    for (y, weights) in rows.iter().enumerate() {
+        for x in 0..new_size.x as usize {
+            let color = weights
+                .iter()
+                .fold(Color::BLACK, |acc, &(sy, w)| acc + horizontal[sy * new_size.x as usize + x] * w);
+
+            out.put_pixel(x as u32, y as u32, encode_rounded(color.to_srgb()));
+        }
+    }
+
+    out
+}
+
+/// Converts a color to 8-bit channels, rounding to the nearest value so that pixels survive
+/// the round trip through linear RGB unchanged.
+fn encode_rounded(c: Color) -> Rgb<u8> {
+    let encode = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgb([encode(c.r), encode(c.g), encode(c.b)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small image in which every pixel has a different color.
+    fn numbered(width: u32, height: u32) -> MandelbrotImage {
+        MandelbrotImage::from_fn(width, height, |x, y| Rgb([x as u8, y as u8, (x + y * width) as u8]))
+    }
+
+    #[test]
+    fn flipping_twice_restores_the_original() {
+        for (width, height) in [(5, 3), (4, 4), (1, 6)] {
+            let original = numbered(width, height);
+
+            for flip in [flip_horizontal, flip_vertical, rotate_180] {
+                let mut im = original.clone();
+                flip(&mut im);
+                flip(&mut im);
+                assert_eq!(im, original);
+            }
+        }
+    }
+
+    #[test]
+    fn corner_pixel_moves_to_the_opposite_corner() {
+        let original = numbered(5, 3);
+        let corner = *original.get_pixel(0, 0);
+
+        let mut im = original.clone();
+        flip_horizontal(&mut im);
+        assert_eq!(*im.get_pixel(4, 0), corner);
+
+        let mut im = original.clone();
+        flip_vertical(&mut im);
+        assert_eq!(*im.get_pixel(0, 2), corner);
+
+        let mut im = original.clone();
+        rotate_180(&mut im);
+        assert_eq!(*im.get_pixel(4, 2), corner);
+        assert_eq!(*im.get_pixel(2, 1), *original.get_pixel(2, 1));
+    }
+
+    #[test]
+    fn overlay_places_and_clips_the_top_image() {
+        let original = numbered(6, 5);
+        let top = MandelbrotImage::from_pixel(3, 3, Rgb([200, 201, 202]));
+        let covered = |x: u32, y: u32, at: Point<u32>| x >= at.x && x < at.x + 3 && y >= at.y && y < at.y + 3;
+
+        // Fully inside, then hanging over the bottom right corner.
+        for at in [Point::new(1, 1), Point::new(4, 3)] {
+            let mut base = original.clone();
+            overlay(&mut base, &top, at);
+
+            for (x, y, pixel) in base.enumerate_pixels() {
+                let expected = if covered(x, y, at) { top.get_pixel(0, 0) } else { original.get_pixel(x, y) };
+                assert_eq!(pixel, expected, "at ({x}, {y}) with the overlay at {at:?}");
+            }
+        }
+
+        let mut base = original.clone();
+        overlay(&mut base, &top, Point::new(6, 0));
+        assert_eq!(base, original);
+    }
+
+    #[test]
+    fn resize_replicates_or_interpolates() {
+        let im = MandelbrotImage::from_raw(2, 2, vec![0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 255]).unwrap();
+
+        let nearest = resize(&im, Point::new(4, 4), ResizeFilter::Nearest);
+        for (x, y, pixel) in nearest.enumerate_pixels() {
+            assert_eq!(pixel, im.get_pixel(x / 2, y / 2), "at ({x}, {y})");
+        }
+
+        let bilinear = resize(&im, Point::new(4, 4), ResizeFilter::Bilinear);
+        for (x, y) in [(0, 0), (3, 0), (0, 3), (3, 3)] {
+            assert_eq!(bilinear.get_pixel(x, y), im.get_pixel(x / 2, y / 2), "corner ({x}, {y})");
+        }
+
+        // Between black and white along the top edge, brighter the closer to white.
+        let [a, b] = [bilinear.get_pixel(1, 0).0[0], bilinear.get_pixel(2, 0).0[0]];
+        assert!(0 < a && a < b && b < 255, "{a} and {b}");
+    }
+}
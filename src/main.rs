@@ -1,6 +1,25 @@
 #[allow(unused_imports)]
 use util::{Color, Complex, MandelbrotImage, Point};
 
+mod animation;
+mod compute;
+mod dzi;
+mod escape;
+mod field;
+#[cfg(feature = "tiff")]
+mod float_tiff;
+mod formula;
+#[cfg(feature = "exr")]
+mod hdr;
+mod image_ops;
+mod math;
+#[cfg(feature = "serde")]
+mod metadata;
+mod navigation;
+mod palette;
+mod perturbation;
+mod render;
+mod shading;
 mod util;
 
 // The resolution of the image in pixels.
@@ -0,0 +1,957 @@
+#![allow(unused)]
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A point in 2D space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    /// Creates a new instance of `Point` given an x and y position.
+    #[inline]
+    pub const fn new(x: T, y: T) -> Point<T> {
+        Self { x, y }
+    }
+
+    /// Maps a function over the x and y parts of this point.
+    #[inline]
+    pub fn map<F: Fn(T) -> U, U>(self, f: F) -> Point<U> {
+        Point::<U> {
+            x: f(self.x),
+            y: f(self.y),
+        }
+    }
+
+    /// Zips two points together.
+    #[inline]
+    pub fn zip<U>(self, rhs: Point<U>) -> Point<(T, U)> {
+        Point::<(T, U)> {
+            x: (self.x, rhs.x),
+            y: (self.y, rhs.y),
+        }
+    }
+
+    /// Combines the x and y parts of two points with a function.
+    #[inline]
+    pub fn zip_map<U, F: Fn(T, U) -> V, V>(self, rhs: Point<U>, f: F) -> Point<V> {
+        Point::<V> {
+            x: f(self.x, rhs.x),
+            y: f(self.y, rhs.y),
+        }
+    }
+}
+
+impl Point<u32> {
+    /// Converts a point from pixel coordinates to uv coordinates given the size of the image.
+    #[inline]
+    pub fn to_uv(self, n: u32) -> Point<f32> {
+        self.map(|v| v as f32 / n as f32)
+    }
+}
+
+impl<T: Default> Default for Point<T> {
+    fn default() -> Self {
+        Self {
+            x: T::default(),
+            y: T::default(),
+        }
+    }
+}
+
+/// A complex number of type `f32`.
+pub type ComplexF32 = Complex<f32>;
+
+/// A complex number of type `f64`.
+pub type ComplexF64 = Complex<f64>;
+
+/// A complex number.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    /// Creates a new instance of `Complex` with the given real and imaginary parts.
+    #[inline]
+    pub const fn new(re: T, im: T) -> Complex<T> {
+        Self { re, im }
+    }
+    
+    /// Maps a function over the real and imaginary parts of a complex number.
+    #[inline]
+    pub fn map<F: Fn(T) -> U, U>(self, f: F) -> Complex<U> {
+        Complex::<U> {
+            re: f(self.re),
+            im: f(self.im),
+        }
+    }
+
+    /// Zips two complex numbers together.
+    #[inline]
+    pub fn zip<U>(self, rhs: Complex<U>) -> Complex<(T, U)> {
+        Complex::<(T, U)> {
+            re: (self.re, rhs.re),
+            im: (self.im, rhs.im),
+        }
+    }
+}
+
+impl<T: Clone + Copy + Mul<T, Output = T> + Add<T, Output = T>> Complex<T> {
+    /// Computes the squared magnitude of a complex number.
+    ///
+    /// This is cheaper than [`Complex::abs`] since it avoids the square root.
+    #[inline]
+    pub fn norm_sqr(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl<T: Neg<Output = T>> Complex<T> {
+    /// Multiplies this complex number by `i`, rotating it a quarter turn counterclockwise.
+    ///
+    /// This only swaps and negates the parts, which is cheaper and exact compared to multiplying by [`Complex::I`].
+    #[inline]
+    pub fn mul_i(self) -> Complex<T> {
+        Complex::new(-self.im, self.re)
+    }
+
+    /// Divides this complex number by `i`, rotating it a quarter turn clockwise.
+    #[inline]
+    pub fn div_i(self) -> Complex<T> {
+        Complex::new(self.im, -self.re)
+    }
+
+    /// Reflects this complex number across the real axis, which is the same as taking its complex conjugate.
+    #[inline]
+    pub fn reflect_real(self) -> Complex<T> {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// Reflects this complex number across the imaginary axis.
+    #[inline]
+    pub fn reflect_imag(self) -> Complex<T> {
+        Complex::new(-self.re, self.im)
+    }
+}
+
+impl<T: Clone + Copy + PartialOrd + Mul<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T>> Complex<T> {
+    /// Whether this complex number lies within the disk of the given `radius` around `center`, including its edge.
+    #[inline]
+    pub fn in_disk(self, center: Complex<T>, radius: T) -> bool {
+        (self - center).norm_sqr() <= radius * radius
+    }
+
+    /// Whether this complex number lies within the axis-aligned rectangle spanned by the corners `min` and `max`,
+    /// including its edges.
+    #[inline]
+    pub fn in_rect(self, min: Complex<T>, max: Complex<T>) -> bool {
+        self.re >= min.re && self.re <= max.re && self.im >= min.im && self.im <= max.im
+    }
+}
+
+impl<T: Clone + Copy + Default + Mul<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T>> Complex<T> {
+    /// Evaluates the polynomial `coeffs[0] + coeffs[1] * z + coeffs[2] * z^2 + ...` at this complex number
+    /// using Horner's method.
+    #[inline]
+    pub fn eval_poly(self, coeffs: &[Complex<T>]) -> Complex<T> {
+        coeffs.iter().rev().fold(Complex::default(), |acc, &coeff| acc * self + coeff)
+    }
+}
+
+impl Complex<f32> {
+    /// Converts this complex number to double precision.
+    #[inline]
+    pub fn to_f64(self) -> Complex<f64> {
+        self.map(|v| v as f64)
+    }
+
+    /// The bits of both parts, normalized so that numbers that compare equal give the same bits.
+    ///
+    /// `-0.0` becomes `0.0` and every `NaN` becomes the same `NaN`, which makes the result usable
+    /// as a hash or cache key where the floats themselves are not.
+    #[inline]
+    pub fn canonical_bits(self) -> (u32, u32) {
+        let canonical = |v: f32| match v {
+            v if v.is_nan() => f32::NAN.to_bits(),
+            0.0 => 0,
+            v => v.to_bits(),
+        };
+
+        (canonical(self.re), canonical(self.im))
+    }
+}
+
+impl Complex<f64> {
+    /// Converts this complex number to single precision, rounding both parts to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(self) -> Complex<f32> {
+        self.map(|v| v as f32)
+    }
+
+    /// The bits of both parts, normalized the same way as `canonical_bits` on `Complex<f32>`.
+    #[inline]
+    pub fn canonical_bits(self) -> (u64, u64) {
+        let canonical = |v: f64| match v {
+            v if v.is_nan() => f64::NAN.to_bits(),
+            0.0 => 0,
+            v => v.to_bits(),
+        };
+
+        (canonical(self.re), canonical(self.im))
+    }
+}
+
+impl Complex<i32> {
+    /// The absolute difference between each part of this Gaussian integer and `other`.
+    ///
+    /// The result is unsigned, so differences that do not fit in an `i32`, such as between
+    /// `i32::MIN` and `i32::MAX`, still come out exact instead of overflowing.
+    #[inline]
+    pub fn abs_sub(self, other: Complex<i32>) -> Complex<u32> {
+        Complex::new(self.re.abs_diff(other.re), self.im.abs_diff(other.im))
+    }
+}
+
+impl<T> From<Point<T>> for Complex<T> {
+    /// Converts a point to a complex number by directly
+    /// mapping the point's x-value to the real component
+    /// and the point's y-value to the imaginary component.
+    #[inline]
+    fn from(value: Point<T>) -> Self {
+        Self {
+            re: value.x,
+            im: value.y,
+        }
+    }
+}
+
+impl<T: Default> Default for Complex<T> {
+    fn default() -> Self {
+        Self {
+            re: T::default(),
+            im: T::default(),
+        }
+    }
+}
+
+impl<T: Add<T, Output = T>> Add for Complex<T> {
+    type Output = Self;
+
+    /// Adds two complex numbers together.
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl<T: Sub<T, Output = T>> Sub for Complex<T> {
+    type Output = Self;
+
+    /// Subtracts one complex number from another.
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+}
+
+impl<T: Clone + Copy + Mul<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T>> Mul for Complex<T> {
+    type Output = Self;
+
+    /// Computes the product of two complex numbers.
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+impl<T: Clone + Copy + Div<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T> + Mul<T, Output = T>> Div for Complex<T> {
+    type Output = Self;
+
+    /// Performs complex division on two complex numbers.
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        Self {
+            re: (self.re * rhs.re + self.im * rhs.im) / (rhs.re * rhs.re + rhs.im * rhs.im),
+            im: (self.im * rhs.re - self.re * rhs.im) / (rhs.re * rhs.re + rhs.im * rhs.im),
+        }
+    }
+}
+
+impl<T> From<(T, T)> for Complex<T> {
+    /// Converts a tuple into a complex number.
+    fn from(value: (T, T)) -> Self {
+        Complex::new(value.0, value.1)
+    }
+}
+
+impl<T> From<Complex<T>> for (T, T) {
+    /// Converts a complex number into a tuple.
+    fn from(value: Complex<T>) -> Self {
+        (value.re, value.im)
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: Hypot> Complex<T> {
+    /// Computes the absolute value (magnitude) of a complex number.
+    #[inline]
+    pub fn abs(self) -> T {
+        self.re.hypotenuse(self.im)
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: Abs + PartialOrd> Complex<T> {
+    /// Computes the Chebyshev norm (max-norm) of a complex number, `max(|re|, |im|)`.
+    #[inline]
+    pub fn abs_max(self) -> T {
+        let re = self.re.absolute();
+        let im = self.im.absolute();
+
+        if re > im { re } else { im }
+    }
+}
+
+
+pub(crate) trait Abs {
+    fn absolute(self) -> Self;
+}
+
+impl Abs for f32 {
+    fn absolute(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+impl Abs for f64 {
+    fn absolute(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+pub(crate) trait Hypot {
+    fn hypotenuse(self, rhs: Self) -> Self;
+}
+
+impl Hypot for f32 {
+    fn hypotenuse(self, rhs: Self) -> Self {
+        f32::hypot(self, rhs)
+    }
+}
+
+impl Hypot for f64 {
+    fn hypotenuse(self, rhs: Self) -> Self {
+        f64::hypot(self, rhs)
+    }
+}
+
+
+macro_rules! impl_op_real {
+    ($($op:tt, $fn:ident, $trait:ident, $doc:tt);*) => {
+        $(
+            impl<T: Clone + Copy + $trait<T, Output = T>> $trait<T> for Complex<T> {
+                type Output = Self;
+
+                #[inline]
+                #[doc = $doc]
+                fn $fn(self, rhs: T) -> Self::Output {
+                    Self {
+                        re: self.re $op rhs,
+                        im: self.im $op rhs,
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_op_real!(
+    +, add, Add, "Adds a real number to both the real and imaginary components of this complex number."; 
+    -, sub, Sub, "Subtracts a real number from both the real and imaginary components of this complex number."; 
+    *, mul, Mul, "Multiplies both the real and imaginary components of this complex number by a real number, effectively scaling it."; 
+    /, div, Div, "Divides both the real and imaginary components of this complex number by a real number, effectively scaling it."
+);
+
+
+/// Asserts in debug builds that `$result` is finite whenever `$input` is,
+/// so that formulas which silently produce infinities or `NaN`s are caught early.
+macro_rules! debug_assert_finite {
+    ($input:expr, $result:expr, $name:literal) => {
+        debug_assert!(
+            !$input.is_finite() || $result.is_finite(),
+            concat!("Complex::", $name, " produced a non-finite result {:?} from the finite input {:?}"),
+            $result,
+            $input,
+        )
+    };
+}
+
+macro_rules! impl_float {
+    ($($t:ident),*) => {
+        $(
+            impl Complex<$t> {
+                pub const ZERO: Self = Self::new(0.0, 0.0);
+                pub const ONE: Self = Self::new(1.0, 0.0);
+                pub const I: Self = Self::new(0.0, 1.0);
+
+                /// Computes the argument (angle) of a complex number in the range `[-pi, pi]`.
+                ///
+                /// The argument of zero is undefined, and `atan2` gives `0` or `±pi` depending on
+                /// the signs of the zeros, so the origin is defined to have an argument of exactly `0`.
+                #[inline]
+                pub fn arg(self) -> $t {
+                    if self.re == 0.0 && self.im == 0.0 {
+                        return 0.0;
+                    }
+
+                    self.im.atan2(self.re)
+                }
+
+                /// A fast approximation of [`Complex::arg`], accurate to within `0.002` radians.
+                ///
+                /// The angle is folded into the first octant, where `atan` is approximated by a low-degree
+                /// polynomial, and then unfolded again. This avoids the cost of `atan2` in per-pixel coloring.
+                #[inline]
+                pub fn arg_fast(self) -> $t {
+                    use std::$t::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+                    let ax = self.re.abs();
+                    let ay = self.im.abs();
+
+                    if ax == 0.0 && ay == 0.0 {
+                        return 0.0;
+                    }
+
+                    let a = ax.min(ay) / ax.max(ay);
+                    let mut r = FRAC_PI_4 * a - a * (a - 1.0) * (0.2447 + 0.0663 * a);
+
+                    if ay > ax {
+                        r = FRAC_PI_2 - r;
+                    }
+
+                    if self.re < 0.0 {
+                        r = PI - r;
+                    }
+
+                    if self.im < 0.0 {
+                        -r
+                    } else {
+                        r
+                    }
+                }
+
+                /// Whether both the real and imaginary parts are finite (neither infinite nor `NaN`).
+                #[inline]
+                pub fn is_finite(self) -> bool {
+                    self.re.is_finite() && self.im.is_finite()
+                }
+
+                /// Computes the magnitude of a complex number without overflowing for large components.
+                ///
+                /// `norm_sqr().sqrt()` overflows to infinity once either component is above `sqrt(MAX)`,
+                /// even though the magnitude itself fits. This divides by the larger component before squaring,
+                /// which is the classic `hypot` algorithm without the extra care `hypot` takes over rounding.
+                #[inline]
+                pub fn abs_robust(self) -> $t {
+                    let a = self.re.abs();
+                    let b = self.im.abs();
+                    let (big, small) = if a > b { (a, b) } else { (b, a) };
+
+                    if big == 0.0 || big.is_infinite() {
+                        return big;
+                    }
+
+                    let ratio = small / big;
+                    big * (1.0 + ratio * ratio).sqrt()
+                }
+
+                /// Returns the complex number of magnitude one that points in the same direction as this one.
+                ///
+                /// Zero has no direction, so like `signum` on real numbers, it returns `1 + 0i` instead of
+                /// zero or `NaN`. This keeps colorings based on the direction continuous around the origin.
+                #[inline]
+                pub fn direction(self) -> Self {
+                    let r = self.abs();
+
+                    if r == 0.0 {
+                        Self::ONE
+                    } else {
+                        self / r
+                    }
+                }
+
+                /// Computes the complex exponential `e^z`.
+                #[inline]
+                pub fn exp(self) -> Self {
+                    let r = self.re.exp();
+
+                    let result = Self {
+                        re: r * self.im.cos(),
+                        im: r * self.im.sin(),
+                    };
+
+                    debug_assert_finite!(self, result, "exp");
+                    result
+                }
+
+                /// Computes the principal natural logarithm, with the imaginary part in `[-pi, pi]`.
+                ///
+                /// The logarithm of zero is `-inf`, which trips a debug assertion.
+                #[inline]
+                pub fn ln(self) -> Self {
+                    let result = Self {
+                        re: self.abs().ln(),
+                        im: self.arg(),
+                    };
+
+                    debug_assert_finite!(self, result, "ln");
+                    result
+                }
+
+                /// Computes the complex sine.
+                #[inline]
+                pub fn sin(self) -> Self {
+                    Self {
+                        re: self.re.sin() * self.im.cosh(),
+                        im: self.re.cos() * self.im.sinh(),
+                    }
+                }
+
+                /// Computes the complex cosine.
+                #[inline]
+                pub fn cos(self) -> Self {
+                    Self {
+                        re: self.re.cos() * self.im.cosh(),
+                        im: -self.re.sin() * self.im.sinh(),
+                    }
+                }
+
+                /// Computes the principal inverse sine, `-i ln(iz + sqrt(1 - z^2))`.
+                ///
+                /// The branch cuts lie along the real axis outside of `[-1, 1]`.
+                /// The real part of the result is in `[-pi/2, pi/2]`.
+                #[inline]
+                pub fn asin(self) -> Self {
+                    let w = (Self::I * self + (Self::ONE - self * self).sqrt()).ln();
+                    Self::new(w.im, -w.re)
+                }
+
+                /// Computes the principal inverse cosine, `pi/2 - asin(z)`.
+                ///
+                /// The branch cuts are the same as for [`Complex::asin`].
+                /// The real part of the result is in `[0, pi]`.
+                #[inline]
+                pub fn acos(self) -> Self {
+                    Self::new(std::$t::consts::FRAC_PI_2, 0.0) - self.asin()
+                }
+
+                /// Computes the principal inverse hyperbolic tangent, `(ln(1 + z) - ln(1 - z)) / 2`.
+                ///
+                /// The branch cuts lie along the real axis outside of `(-1, 1)`. The function is infinite at `±1`,
+                /// which is returned as a real infinity of the same sign rather than taking the logarithm of zero.
+                #[inline]
+                pub fn atanh(self) -> Self {
+                    if self.im == 0.0 && self.re.abs() == 1.0 {
+                        return Self::new(self.re * $t::INFINITY, 0.0);
+                    }
+
+                    ((Self::ONE + self).ln() - (Self::ONE - self).ln()) * 0.5
+                }
+
+                /// Computes the principal square root, which always has a non-negative real part.
+                #[inline]
+                pub fn sqrt(self) -> Self {
+                    let r = self.abs();
+                    let re = ((r + self.re) * 0.5).sqrt();
+                    let im = ((r - self.re) * 0.5).sqrt();
+
+                    let result = Self {
+                        re,
+                        im: if self.im < 0.0 { -im } else { im },
+                    };
+
+                    debug_assert_finite!(self, result, "sqrt");
+                    result
+                }
+
+                /// Raises this number to the integer power `n` by repeated squaring.
+                ///
+                /// Large exponents can overflow, and a product of overflowed parts can be `NaN` as easily as infinite,
+                /// so any non-finite result of a finite input is reported as `inf + inf i`. The same goes for zero
+                /// raised to a negative power. Use [`Complex::checked_powi`] to catch this instead.
+                #[inline]
+                pub fn powi(self, n: i32) -> Self {
+                    let mut base = if n < 0 { Self::ONE / self } else { self };
+                    let mut e = n.unsigned_abs();
+                    let mut result = Self::ONE;
+
+                    while e > 0 {
+                        if e & 1 == 1 {
+                            result = result * base;
+                        }
+
+                        base = base * base;
+                        e >>= 1;
+                    }
+
+                    if self.is_finite() && !result.is_finite() {
+                        Self::new($t::INFINITY, $t::INFINITY)
+                    } else {
+                        result
+                    }
+                }
+
+                /// Like [`Complex::powi`], but returns `None` instead of an infinite result.
+                #[inline]
+                pub fn checked_powi(self, n: i32) -> Option<Self> {
+                    let result = self.powi(n);
+                    result.is_finite().then_some(result)
+                }
+
+                /// Computes both `z^n` and its derivative `n z^(n-1)`, as needed by distance estimation for `z^n + c`.
+                ///
+                /// `z^(n-1)` is computed once with [`Complex::powi`] and shared between the two, so this costs
+                /// hardly more than the power alone, except for `n = i32::MIN`, where it is found as `z^n / z` since
+                /// `n - 1` does not fit in an `i32`. Results that overflow follow the same rules as [`Complex::powi`].
+                #[inline]
+                pub fn powi_with_deriv(self, n: i32) -> (Self, Self) {
+                    if n == 0 {
+                        return (Self::ONE, Self::ZERO);
+                    }
+
+                    let lower = match n.checked_sub(1) {
+                        Some(lower) => self.powi(lower),
+                        None if self.re == 0.0 && self.im == 0.0 => Self::new($t::INFINITY, $t::INFINITY),
+                        None => self.powi(n) / self,
+                    };
+                    let power = lower * self;
+                    let power = if self.is_finite() && !power.is_finite() { Self::new($t::INFINITY, $t::INFINITY) } else { power };
+
+                    (power, lower * n as $t)
+                }
+
+                /// Raises this number to the real power `x`, using the principal value of the argument.
+                ///
+                /// Like [`Complex::powi`], a finite input that overflows gives `inf + inf i`.
+                #[inline]
+                pub fn powf(self, x: $t) -> Self {
+                    let r = self.abs().powf(x);
+                    let theta = self.arg() * x;
+                    let result = Self::new(r * theta.cos(), r * theta.sin());
+
+                    if self.is_finite() && !result.is_finite() {
+                        Self::new($t::INFINITY, $t::INFINITY)
+                    } else {
+                        result
+                    }
+                }
+
+                /// Formats this number as `re ± im i`, with both parts right-aligned to `width` characters
+                /// and given `precision` digits after the decimal point.
+                ///
+                /// The sign of the imaginary part is always written between the two parts, so as long as
+                /// they fit within `width`, every value comes out the same length and lines up in a table.
+                pub fn to_aligned_string(self, width: usize, precision: usize) -> String {
+                    let sign = if self.im < 0.0 { '-' } else { '+' };
+                    format!("{:>width$.precision$} {sign} {:>width$.precision$}i", self.re, self.im.abs())
+                }
+            }
+        )*
+    }
+}
+
+impl_float!(f32, f64);
+
+/// Raises the positive real number `a` to the complex power `z`, computed as `exp(z * ln(a))`.
+#[inline]
+pub fn real_pow(a: f32, z: ComplexF32) -> ComplexF32 {
+    (z * a.ln()).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abs_max_takes_the_larger_component() {
+        assert_eq!(Complex::new(3.0f32, -4.0).abs_max(), 4.0);
+        assert_eq!(Complex::new(-5.0f64, 2.0).abs_max(), 5.0);
+        assert_eq!(Complex::new(0.0f32, 0.0).abs_max(), 0.0);
+    }
+
+    #[test]
+    fn eval_poly_matches_the_mandelbrot_step() {
+        let z = Complex::new(0.3f32, -0.7);
+        let c = Complex::new(-0.4f32, 0.6);
+        let zero = Complex::new(0.0, 0.0);
+        let one = Complex::new(1.0, 0.0);
+
+        assert_eq!(z.eval_poly(&[c, zero, one]).canonical_bits(), (z * z + c).canonical_bits());
+        assert_eq!(z.eval_poly(&[]).canonical_bits(), zero.canonical_bits());
+    }
+
+    #[test]
+    fn arg_fast_is_accurate_in_every_quadrant() {
+        use std::f64::consts::{PI, TAU};
+
+        for k in 0..3600 {
+            let angle = k as f64 / 3600.0 * TAU - PI;
+
+            for radius in [1e-3, 1.0, 250.0] {
+                let z = Complex::new(radius * angle.cos(), radius * angle.sin());
+                let error = (z.arg_fast() - z.arg() + PI).rem_euclid(TAU) - PI;
+                assert!(error.abs() < 0.002, "error {error} at angle {angle}");
+
+                let z = z.to_f32();
+                let error = (z.arg_fast() as f64 - z.arg() as f64 + PI).rem_euclid(TAU) - PI;
+                assert!(error.abs() < 0.002, "f32 error {error} at angle {angle}");
+            }
+        }
+    }
+
+    #[test]
+    fn real_pow_of_e_is_exp() {
+        use std::f32::consts::E;
+
+        let z = real_pow(E, Complex::new(1.0, 0.0));
+        assert!((z.re - E).abs() < 1e-5 && z.im.abs() < 1e-6, "{z:?}");
+
+        // 2^(i*pi / ln 2) = e^(i*pi) = -1
+        let z = real_pow(2.0, Complex::new(0.0, std::f32::consts::PI / 2f32.ln()));
+        assert!((z.re + 1.0).abs() < 1e-5 && z.im.abs() < 1e-5, "{z:?}");
+    }
+
+    #[test]
+    fn precision_conversions_round_trip() {
+        let z = Complex::new(0.1f32, -2.5e-3);
+        assert_eq!(z.to_f64().to_f32().canonical_bits(), z.canonical_bits());
+
+        // 0.1 has no exact binary representation, so widening the f32 value does not give the f64 one.
+        assert_ne!(z.to_f64().re, 0.1);
+
+        // Going down to f32 drops everything past its 24 bits of mantissa.
+        let w = Complex::new(1.0 + 1e-10, 1.0 / 3.0);
+        let narrowed = w.to_f32();
+        assert_eq!(narrowed.re, 1.0);
+        assert_ne!(narrowed.to_f64().im, w.im);
+        assert!((narrowed.to_f64().im - w.im).abs() < 1e-7);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Complex::ln produced a non-finite result")]
+    fn ln_of_zero_trips_the_finite_assertion() {
+        ComplexF32::ZERO.ln();
+    }
+
+    #[test]
+    fn finite_assertion_allows_non_finite_inputs() {
+        let z = Complex::new(f32::INFINITY, 0.0).exp();
+        assert!(!z.is_finite());
+    }
+
+    #[test]
+    fn abs_robust_does_not_overflow_near_sqrt_max() {
+        let v = 1.5 * f32::MAX.sqrt();
+        let z = Complex::new(v, -v);
+
+        assert!(z.norm_sqr().sqrt().is_infinite());
+
+        let abs = z.abs_robust();
+        assert!(abs.is_finite());
+        assert!((abs / (v * 2f32.sqrt()) - 1.0).abs() < 1e-6, "{abs}");
+
+        let z = Complex::new(3.0f64, 4.0);
+        assert!((z.abs_robust() - 5.0).abs() < 1e-12);
+        assert_eq!(Complex::new(0.0f32, 0.0).abs_robust(), 0.0);
+    }
+
+    #[test]
+    fn direction_has_unit_length() {
+        let d = Complex::new(3.0f32, -4.0).direction();
+        assert!((d.re - 0.6).abs() < 1e-6 && (d.im + 0.8).abs() < 1e-6, "{d:?}");
+
+        let d = Complex::new(0.0f64, 0.0).direction();
+        assert_eq!((d.re, d.im), (1.0, 0.0));
+    }
+
+    #[test]
+    fn disk_and_rect_containment() {
+        let center = Complex::new(-1.0f32, 0.0);
+        assert!(Complex::new(-1.1, 0.1).in_disk(center, 0.25));
+        assert!(Complex::new(-0.75, 0.0).in_disk(center, 0.25));
+        assert!(!Complex::new(-0.7, 0.0).in_disk(center, 0.25));
+
+        let (min, max) = (Complex::new(0, -2), Complex::new(4, 2));
+        assert!(Complex::new(2, 0).in_rect(min, max));
+        assert!(Complex::new(4, -2).in_rect(min, max));
+        assert!(!Complex::new(5, 0).in_rect(min, max));
+        assert!(!Complex::new(1, 3).in_rect(min, max));
+    }
+
+    #[test]
+    fn zip_map_adds_points() {
+        let add = |a: Point<i32>, b: Point<i32>| a.zip_map(b, |a, b| a + b);
+
+        assert_eq!(add(Point::new(3, -4), Point::new(10, 20)), Point::new(13, 16));
+        assert_eq!(add(Point::new(3, -4), Point::default()), Point::new(3, -4));
+        assert_eq!(Point::new(1u32, 2).zip(Point::new('a', 'b')), Point::new((1, 'a'), (2, 'b')));
+    }
+
+    #[test]
+    fn inverse_functions_undo_their_forward_functions() {
+        for z in [Complex::new(0.3f64, 0.2), Complex::new(-0.8, 1.5), Complex::new(2.0, -0.5), Complex::new(0.0, -3.0)] {
+            let w = z.asin().sin();
+            assert!((w - z).norm_sqr().sqrt() < 1e-12, "sin(asin({z:?})) = {w:?}");
+
+            let w = z.acos().cos();
+            assert!((w - z).norm_sqr().sqrt() < 1e-12, "cos(acos({z:?})) = {w:?}");
+        }
+
+        let z = Complex::new(0.5f32, -0.25).atanh();
+        let tanh = ((z * 2.0).exp() - ComplexF32::ONE) / ((z * 2.0).exp() + ComplexF32::ONE);
+        assert!((tanh - Complex::new(0.5, -0.25)).norm_sqr().sqrt() < 1e-6, "{tanh:?}");
+    }
+
+    #[test]
+    fn atanh_is_infinite_at_one() {
+        let z = Complex::new(1.0f32, 0.0).atanh();
+        assert_eq!((z.re, z.im), (f32::INFINITY, 0.0));
+
+        let z = Complex::new(-1.0f64, 0.0).atanh();
+        assert_eq!((z.re, z.im), (f64::NEG_INFINITY, 0.0));
+    }
+
+    #[test]
+    fn aligned_strings_have_the_same_width() {
+        let a = Complex::new(-0.75f32, 0.1).to_aligned_string(9, 4);
+        let b = Complex::new(12.5f32, -3.25).to_aligned_string(9, 4);
+
+        assert_eq!(a, "  -0.7500 +    0.1000i");
+        assert_eq!(b, "  12.5000 -    3.2500i");
+        assert_eq!(a.len(), b.len());
+    }
+
+    #[test]
+    fn arg_of_zero_is_zero_for_every_signed_zero() {
+        for (re, im) in [(0.0f32, 0.0), (-0.0, 0.0), (0.0, -0.0), (-0.0, -0.0)] {
+            let arg = Complex::new(re, im).arg();
+            assert!(arg == 0.0 && arg.is_sign_positive(), "arg({re:?}, {im:?}) = {arg}");
+            assert_eq!(Complex::new(re as f64, im as f64).arg(), 0.0);
+        }
+
+        assert_eq!(Complex::new(-1.0f32, 0.0).arg(), std::f32::consts::PI);
+    }
+
+    #[test]
+    fn i_squared_is_minus_one() {
+        assert_eq!((ComplexF32::I * ComplexF32::I).canonical_bits(), (ComplexF32::ZERO - ComplexF32::ONE).canonical_bits());
+        assert_eq!((ComplexF64::I * ComplexF64::I).canonical_bits(), (ComplexF64::ZERO - ComplexF64::ONE).canonical_bits());
+        assert_eq!((ComplexF32::ZERO + ComplexF32::ONE).canonical_bits(), ComplexF32::ONE.canonical_bits());
+    }
+
+    #[test]
+    fn checked_powi_rejects_overflow() {
+        assert!(Complex::new(1.5f32, 0.5).checked_powi(100_000).is_none());
+        assert!(Complex::new(1.01f64, 0.0).checked_powi(i32::MAX).is_none());
+
+        let z = Complex::new(0.0f32, 1.0).checked_powi(2).unwrap();
+        assert!((z.re + 1.0).abs() < 1e-6 && z.im.abs() < 1e-6, "{z:?}");
+
+        let z = Complex::new(0.5f32, 0.5).checked_powi(100_000).unwrap();
+        assert_eq!(z.norm_sqr(), 0.0);
+    }
+
+    #[test]
+    fn signed_zeros_and_nans_have_the_same_canonical_bits() {
+        assert_eq!(Complex::new(0.0f32, -0.0).canonical_bits(), Complex::new(-0.0f32, 0.0).canonical_bits());
+        assert_eq!(Complex::new(-0.0f64, -0.0).canonical_bits(), (0, 0));
+        assert_eq!(Complex::new(f32::NAN, 1.0).canonical_bits(), Complex::new(-f32::NAN, 1.0).canonical_bits());
+        assert_ne!(Complex::new(1.0f32, 0.0).canonical_bits(), Complex::new(-1.0f32, 0.0).canonical_bits());
+    }
+
+    #[test]
+    fn rotations_by_i_match_multiplication() {
+        for z in [ComplexF32::new(0.0, 0.0), ComplexF32::new(1.0, 0.0), ComplexF32::new(-2.5, 0.75), ComplexF32::new(3.0, -4.0)] {
+            assert_eq!(z.mul_i().canonical_bits(), (z * ComplexF32::I).canonical_bits(), "{z:?}");
+            assert_eq!(z.div_i().canonical_bits(), (z / ComplexF32::I).canonical_bits(), "{z:?}");
+            assert_eq!(z.mul_i().div_i().canonical_bits(), z.canonical_bits(), "{z:?}");
+        }
+    }
+
+    #[test]
+    fn abs_sub_does_not_overflow() {
+        let parts = |z: Complex<u32>| (z.re, z.im);
+
+        assert_eq!(parts(Complex::new(3, -4).abs_sub(Complex::new(-2, 5))), (5, 9));
+        assert_eq!(parts(Complex::new(-2, 5).abs_sub(Complex::new(3, -4))), (5, 9));
+        assert_eq!(parts(Complex::new(7, 7).abs_sub(Complex::new(7, 7))), (0, 0));
+
+        // `i32::MAX - i32::MIN` does not fit in an `i32`.
+        let wide = Complex::new(i32::MIN, i32::MAX).abs_sub(Complex::new(i32::MAX, i32::MIN));
+        assert_eq!(parts(wide), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn reflections_flip_one_part() {
+        let z = ComplexF32::new(1.5, -2.0);
+        assert_eq!(z.reflect_real().canonical_bits(), ComplexF32::new(1.5, 2.0).canonical_bits());
+        assert_eq!(z.reflect_imag().canonical_bits(), ComplexF32::new(-1.5, -2.0).canonical_bits());
+
+        // Both reflections together are a half turn, and each one undoes itself.
+        assert_eq!(z.reflect_real().reflect_imag().canonical_bits(), (ComplexF32::ZERO - z).canonical_bits());
+        assert_eq!(z.reflect_real().reflect_real().canonical_bits(), z.canonical_bits());
+        assert_eq!(z.reflect_imag().reflect_imag().canonical_bits(), z.canonical_bits());
+
+        let p = Complex::new(3, 4).reflect_imag();
+        assert_eq!((p.re, p.im), (-3, 4));
+    }
+
+    #[test]
+    fn square_with_derivative() {
+        for z in [ComplexF32::new(0.0, 0.0), ComplexF32::new(1.5, -0.5), ComplexF32::new(-3.0, 2.0)] {
+            let (power, deriv) = z.powi_with_deriv(2);
+            assert_eq!(power.canonical_bits(), (z * z).canonical_bits(), "{z:?}");
+            assert_eq!(deriv.canonical_bits(), (z * 2.0).canonical_bits(), "{z:?}");
+        }
+
+        let (power, deriv) = ComplexF32::new(2.0, 1.0).powi_with_deriv(0);
+        assert_eq!((power.canonical_bits(), deriv.canonical_bits()), (ComplexF32::ONE.canonical_bits(), ComplexF32::ZERO.canonical_bits()));
+    }
+
+    #[test]
+    fn powi_with_deriv_handles_the_smallest_exponent() {
+        let n = i32::MIN;
+
+        let (power, deriv) = ComplexF32::ONE.powi_with_deriv(n);
+        assert_eq!((power.canonical_bits(), deriv.canonical_bits()), (ComplexF32::ONE.canonical_bits(), ComplexF32::new(n as f32, 0.0).canonical_bits()));
+
+        // `2^31` is a multiple of four, so `i^n` is `1` and `n i^(n-1)` is `-n i`.
+        let (power, deriv) = ComplexF32::I.powi_with_deriv(n);
+        assert_eq!((power.canonical_bits(), deriv.canonical_bits()), (ComplexF32::ONE.canonical_bits(), ComplexF32::new(0.0, -(n as f32)).canonical_bits()));
+
+        let (power, deriv) = ComplexF32::new(2.0, 0.0).powi_with_deriv(n);
+        assert_eq!((power.canonical_bits(), deriv.canonical_bits()), (ComplexF32::ZERO.canonical_bits(), ComplexF32::ZERO.canonical_bits()));
+
+        for z in [ComplexF32::new(0.5, 0.0), ComplexF32::ZERO] {
+            let (power, deriv) = z.powi_with_deriv(n);
+            assert!(power.re == f32::INFINITY && power.im == f32::INFINITY, "{power:?} for {z:?}");
+            assert!(!deriv.is_finite(), "{deriv:?} for {z:?}");
+        }
+    }
+}
@@ -0,0 +1,127 @@
+#![allow(unused)]
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::Viewport;
+use crate::palette::Palette;
+use crate::render::RenderSettings;
+use crate::util::MandelbrotImage;
+
+/// Everything needed to reproduce a saved render.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderMetadata {
+    /// The version of this crate that produced the render.
+    pub version: String,
+    /// The region of the complex plane that was rendered.
+    pub viewport: Viewport,
+    /// The settings the image was rendered with.
+    pub settings: RenderSettings,
+    /// The name of the palette the image was colored with, if it has one.
+    pub palette: Option<String>,
+}
+
+/// An error that occurred while saving or loading a render and its metadata.
+#[derive(Debug)]
+pub enum MetadataError {
+    Image(image::ImageError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataError::Image(e) => write!(f, "failed to save image: {e}"),
+            MetadataError::Io(e) => write!(f, "failed to access metadata file: {e}"),
+            MetadataError::Json(e) => write!(f, "invalid metadata: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+impl From<image::ImageError> for MetadataError {
+    fn from(e: image::ImageError) -> Self {
+        MetadataError::Image(e)
+    }
+}
+
+impl From<std::io::Error> for MetadataError {
+    fn from(e: std::io::Error) -> Self {
+        MetadataError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for MetadataError {
+    fn from(e: serde_json::Error) -> Self {
+        MetadataError::Json(e)
+    }
+}
+
+/// The path of the metadata file that accompanies the image at `path`.
+#[inline]
+pub fn sidecar_path(path: &str) -> String {
+    format!("{path}.json")
+}
+
+/// Saves `im` to `path` along with a `<path>.json` file recording how it was rendered.
+pub fn save_with_metadata(
+    im: &MandelbrotImage,
+    path: &str,
+    settings: &RenderSettings,
+    viewport: &Viewport,
+    palette: &Palette,
+) -> Result<(), MetadataError> {
+    im.save(path)?;
+
+    let metadata = RenderMetadata {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        viewport: *viewport,
+        settings: settings.clone(),
+        palette: palette.name().map(str::to_owned),
+    };
+
+    let file = BufWriter::new(File::create(sidecar_path(path))?);
+    serde_json::to_writer_pretty(file, &metadata)?;
+
+    Ok(())
+}
+
+/// Loads the metadata that was saved alongside the image at `path` by [`save_with_metadata`].
+pub fn load_metadata(path: &str) -> Result<RenderMetadata, MetadataError> {
+    let file = BufReader::new(File::open(sidecar_path(path))?);
+    Ok(serde_json::from_reader(file)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_round_trips_viewport_and_settings() {
+        let viewport = Viewport::new(crate::util::Complex::new(-0.743, 0.131), 0.004);
+        let mut settings = RenderSettings { cycles: 3.0, min_iter: 12, ..RenderSettings::default() };
+        settings.compute.max_iter = 2000;
+        settings.compute.smooth = true;
+
+        let im = MandelbrotImage::new(2, 2);
+        let path = std::env::temp_dir().join("mandelbrot_test_metadata.png");
+        let path = path.to_str().unwrap();
+        save_with_metadata(&im, path, &settings, &viewport, &Palette::grayscale()).unwrap();
+
+        let metadata = load_metadata(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(sidecar_path(path)).unwrap();
+
+        assert_eq!(metadata.viewport.center.canonical_bits(), viewport.center.canonical_bits());
+        assert_eq!(metadata.viewport.scale, viewport.scale);
+        assert_eq!(metadata.viewport.stretch_x, viewport.stretch_x);
+        assert_eq!(serde_json::to_value(&metadata.settings).unwrap(), serde_json::to_value(&settings).unwrap());
+        assert_eq!(metadata.palette.as_deref(), Some("grayscale"));
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+    }
+}
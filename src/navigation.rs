@@ -0,0 +1,130 @@
+#![allow(unused)]
+
+use crate::compute::Viewport;
+
+/// The viewports an interactive viewer has visited, for moving back and forward between them like a browser.
+///
+/// Only the most recent `max_depth` viewports are kept, with the oldest dropped first. The home viewport
+/// is kept separately, so [`NavHistory::reset_to_home`] works however much history has been dropped.
+#[derive(Debug, Clone)]
+pub struct NavHistory {
+    entries: Vec<Viewport>,
+    current: usize,
+    home: Viewport,
+    max_depth: usize,
+}
+
+impl NavHistory {
+    /// Creates a new `NavHistory` that starts at `home` and remembers up to `max_depth` viewports.
+    ///
+    /// The current viewport always counts towards the depth, so a `max_depth` of `0` is treated as `1`.
+    pub fn new(home: Viewport, max_depth: usize) -> NavHistory {
+        NavHistory {
+            entries: vec![home],
+            current: 0,
+            home,
+            max_depth: max_depth.max(1),
+        }
+    }
+
+    /// The viewport that is currently shown.
+    #[inline]
+    pub fn current(&self) -> Viewport {
+        self.entries[self.current]
+    }
+
+    /// The viewport that [`NavHistory::reset_to_home`] returns to.
+    #[inline]
+    pub fn home(&self) -> Viewport {
+        self.home
+    }
+
+    /// Navigates to `viewport`, discarding any viewports that could have been returned to with [`NavHistory::forward`].
+    pub fn push(&mut self, viewport: Viewport) {
+        self.entries.truncate(self.current + 1);
+        self.entries.push(viewport);
+
+        if self.entries.len() > self.max_depth {
+            self.entries.drain(..self.entries.len() - self.max_depth);
+        }
+
+        self.current = self.entries.len() - 1;
+    }
+
+    /// Steps back to the previous viewport and returns it, or `None` if there is nothing further back.
+    pub fn back(&mut self) -> Option<Viewport> {
+        self.current = self.current.checked_sub(1)?;
+        Some(self.current())
+    }
+
+    /// Steps forward again after [`NavHistory::back`] and returns the viewport, or `None` if there is nothing ahead.
+    pub fn forward(&mut self) -> Option<Viewport> {
+        if self.current + 1 >= self.entries.len() {
+            return None;
+        }
+
+        self.current += 1;
+        Some(self.current())
+    }
+
+    /// Navigates back to the home viewport.
+    ///
+    /// This is recorded like any other navigation, so [`NavHistory::back`] returns to where the viewer was before.
+    pub fn reset_to_home(&mut self) -> Viewport {
+        self.push(self.home);
+        self.home
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::Complex;
+
+    /// A viewport told apart from the others by its scale.
+    fn view(n: u32) -> Viewport {
+        Viewport::new(Complex::new(0.0, 0.0), n as f32)
+    }
+
+    #[test]
+    fn back_and_forward_walk_the_history() {
+        let mut history = NavHistory::new(view(0), 10);
+        assert!(history.back().is_none() && history.forward().is_none());
+
+        history.push(view(1));
+        history.push(view(2));
+        assert_eq!(history.back().map(|v| v.scale), Some(1.0));
+        assert_eq!(history.back().map(|v| v.scale), Some(0.0));
+        assert!(history.back().is_none());
+        assert_eq!(history.forward().map(|v| v.scale), Some(1.0));
+
+        // Navigating somewhere new drops the viewport that was ahead.
+        history.push(view(3));
+        assert!(history.forward().is_none());
+        assert_eq!(history.back().map(|v| v.scale), Some(1.0));
+        assert_eq!(history.forward().map(|v| v.scale), Some(3.0));
+
+        assert_eq!(history.reset_to_home().scale, 0.0);
+        assert_eq!(history.back().map(|v| v.scale), Some(3.0));
+    }
+
+    #[test]
+    fn history_is_capped_at_max_depth() {
+        let mut history = NavHistory::new(view(0), 3);
+        for n in 1..=5 {
+            history.push(view(n));
+        }
+
+        assert_eq!(history.current().scale, 5.0);
+        assert_eq!(history.back().map(|v| v.scale), Some(4.0));
+        assert_eq!(history.back().map(|v| v.scale), Some(3.0));
+        assert!(history.back().is_none());
+        assert_eq!(history.home().scale, 0.0);
+
+        let mut history = NavHistory::new(view(0), 0);
+        history.push(view(1));
+        assert_eq!(history.current().scale, 1.0);
+        assert!(history.back().is_none());
+    }
+}
@@ -0,0 +1,327 @@
+#![allow(unused)]
+
+use image::{ImageResult, Rgb};
+
+use crate::util::{BlendSpace, Color, ColorA};
+
+/// A gradient of colors, evenly spaced over the range `[0, 1]`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    colors: Vec<Color>,
+    name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    blend: BlendSpace,
+}
+
+impl Palette {
+    /// Creates a new `Palette` from a list of colors.
+    ///
+    /// The first color sits at `t = 0`, the last at `t = 1`, and the rest are spaced evenly between them.
+    /// Panics if `colors` is empty.
+    pub fn new(colors: Vec<Color>) -> Palette {
+        assert!(!colors.is_empty(), "a palette needs at least one color");
+        Palette {
+            colors,
+            name: None,
+            blend: BlendSpace::default(),
+        }
+    }
+
+    /// Gives this palette a name, which is recorded alongside saved renders.
+    pub fn with_name(mut self, name: &str) -> Palette {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    /// Sets the color space that neighboring colors are interpolated in, which is sRGB by default.
+    pub fn with_blend(mut self, blend: BlendSpace) -> Palette {
+        self.blend = blend;
+        self
+    }
+
+    /// The color space that neighboring colors are interpolated in.
+    #[inline]
+    pub fn blend(&self) -> BlendSpace {
+        self.blend
+    }
+
+    /// The name of this palette, if it has one.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// A simple black to white gradient.
+    pub fn grayscale() -> Palette {
+        Palette::new(vec![Color::BLACK, Color::WHITE]).with_name("grayscale")
+    }
+
+    /// Loads a palette from the first row of pixels of the image at `path`.
+    ///
+    /// This lets gradients be designed in an image editor and exported as a strip that is one pixel tall.
+    pub fn from_image_row(path: &str) -> ImageResult<Palette> {
+        let im = image::open(path)?.to_rgb8();
+        let colors = (0..im.width()).map(|x| (*im.get_pixel(x, 0)).into()).collect();
+
+        Ok(Palette::new(colors))
+    }
+
+    /// The colors that make up this palette.
+    #[inline]
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Samples the palette at `t`, interpolating between neighboring colors in the palette's [`BlendSpace`].
+    ///
+    /// Values of `t` outside of `[0, 1]` are clamped.
+    pub fn sample(&self, t: f32) -> Color {
+        sample_stops(&self.colors, t, |a, b, t| a.lerp_in(b, t, self.blend))
+    }
+
+    /// Samples the palette at `t`, wrapping around so that the palette repeats every unit of `t`.
+    #[inline]
+    pub fn sample_cyclic(&self, t: f32) -> Color {
+        self.sample(t.rem_euclid(1.0))
+    }
+
+    /// Samples the palette at `t` in `[0, 1]`, with the palette repeated `cycles` times over that range.
+    ///
+    /// Each repetition ends on the last color of the palette, so with `cycles = 1` this is the same as [`Palette::sample`].
+    #[inline]
+    pub fn sample_repeated(&self, t: f32, cycles: f32) -> Color {
+        self.sample(repeat(t, cycles))
+    }
+
+    /// Blends between the palettes `a` and `b` by `t`, giving `a` at `t = 0` and `b` at `t = 1`.
+    ///
+    /// The palettes may have different numbers of colors, so both are first resampled to a common set of stops.
+    /// Where possible these include every stop of both palettes, so the ends of the crossfade match them exactly,
+    /// but the number of stops is capped at [`CROSSFADE_MAX_STOPS`]. The result blends in the same space as `a`.
+    pub fn crossfade(a: &Palette, b: &Palette, t: f32) -> Palette {
+        let segments_a = a.colors.len() - 1;
+        let segments_b = b.colors.len() - 1;
+        let segments = lcm(segments_a, segments_b).min(CROSSFADE_MAX_STOPS - 1);
+
+        let colors = (0..=segments)
+            .map(|i| {
+                let x = if segments == 0 { 0.0 } else { i as f32 / segments as f32 };
+                let from = if segments_a == segments { a.colors[i] } else { a.sample(x) };
+                let to = if segments_b == segments { b.colors[i] } else { b.sample(x) };
+
+                from.lerp(to, t)
+            })
+            .collect();
+
+        Palette::new(colors).with_blend(a.blend)
+    }
+
+    /// Precomputes `n` evenly spaced samples of this palette, from `t = 0` to `t = 1`.
+    ///
+    /// Looking colors up in the table is cheaper than interpolating for every pixel,
+    /// at the cost of rounding `t` to the nearest of the `n` entries. Panics if `n` is `0`.
+    pub fn to_lut(&self, n: usize) -> Vec<Rgb<u8>> {
+        match n {
+            0 => panic!("a palette table needs at least one entry"),
+            1 => vec![self.sample(0.0).into()],
+            _ => (0..n).map(|i| self.sample(i as f32 / (n - 1) as f32).into()).collect(),
+        }
+    }
+}
+
+/// The largest number of stops that [`Palette::crossfade`] resamples two palettes to.
+pub const CROSSFADE_MAX_STOPS: usize = 256;
+
+/// The least common multiple of `a` and `b`, where a zero is ignored rather than making the result zero.
+fn lcm(a: usize, b: usize) -> usize {
+    if a == 0 || b == 0 {
+        return a.max(b);
+    }
+
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+
+    a / x * b
+}
+
+/// Maps `t` in `[0, 1]` to where it falls within a palette that is repeated `cycles` times over that range.
+///
+/// Each repetition ends on `1`, so with `cycles = 1` this leaves `t` unchanged.
+#[inline]
+pub fn repeat(t: f32, cycles: f32) -> f32 {
+    let x = t * cycles;
+
+    if x <= 0.0 {
+        0.0
+    } else {
+        x - x.ceil() + 1.0
+    }
+}
+
+/// Looks up `t` in a table created by [`Palette::to_lut`], rounding to the nearest entry.
+///
+/// Panics if `lut` is empty.
+#[inline]
+pub fn sample_lut(lut: &[Rgb<u8>], t: f32) -> Rgb<u8> {
+    assert!(!lut.is_empty(), "a palette table needs at least one entry");
+    let i = (t.clamp(0.0, 1.0) * (lut.len() - 1) as f32).round() as usize;
+    lut[i]
+}
+
+/// A gradient of colors with opacity, evenly spaced over the range `[0, 1]`.
+///
+/// This is the same as a [`Palette`], except that regions can be made partially transparent,
+/// which allows several renders to be layered on top of each other.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaletteA {
+    colors: Vec<ColorA>,
+}
+
+impl PaletteA {
+    /// Creates a new `PaletteA` from a list of colors.
+    ///
+    /// The colors are spaced the same way as in [`Palette::new`]. Panics if `colors` is empty.
+    pub fn new(colors: Vec<ColorA>) -> PaletteA {
+        assert!(!colors.is_empty(), "a palette needs at least one color");
+        PaletteA { colors }
+    }
+
+    /// The colors that make up this palette.
+    #[inline]
+    pub fn colors(&self) -> &[ColorA] {
+        &self.colors
+    }
+
+    /// Samples the palette at `t`, linearly interpolating between neighboring colors, including their opacity.
+    ///
+    /// Values of `t` outside of `[0, 1]` are clamped.
+    pub fn sample(&self, t: f32) -> ColorA {
+        sample_stops(&self.colors, t, ColorA::lerp)
+    }
+
+    /// Samples the palette at `t`, wrapping around so that the palette repeats every unit of `t`.
+    #[inline]
+    pub fn sample_cyclic(&self, t: f32) -> ColorA {
+        self.sample(t.rem_euclid(1.0))
+    }
+}
+
+impl From<Palette> for PaletteA {
+    /// Converts a palette to a fully opaque `PaletteA`.
+    fn from(v: Palette) -> Self {
+        PaletteA::new(v.colors.into_iter().map(ColorA::from).collect())
+    }
+}
+
+/// A pair of palettes that color the outside and the inside of the set independently.
+///
+/// The exterior palette is looked up by escape count, as usual, and the interior palette by one of the
+/// [`InteriorMetric`](crate::render::InteriorMetric)s, see [`colorize_dual`](crate::render::colorize_dual).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DualPalette {
+    /// The palette for points that escape.
+    pub exterior: Palette,
+    /// The palette for points inside the set.
+    pub interior: Palette,
+}
+
+/// Samples a list of evenly spaced stops at `t`, interpolating between neighbors with `lerp`.
+fn sample_stops<T: Copy>(stops: &[T], t: f32, lerp: impl Fn(T, T, f32) -> T) -> T {
+    let n = stops.len();
+
+    if n == 1 {
+        return stops[0];
+    }
+
+    let x = t.clamp(0.0, 1.0) * (n - 1) as f32;
+    let i = (x as usize).min(n - 2);
+
+    lerp(stops[i], stops[i + 1], x - i as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_row_palette_interpolates_between_pixels() {
+        let strip = image::RgbImage::from_raw(4, 1, vec![0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 255]).unwrap();
+        let path = std::env::temp_dir().join("mandelbrot_test_palette_row.png");
+        strip.save(&path).unwrap();
+
+        let palette = Palette::from_image_row(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(palette.colors().len(), 4);
+        assert!(palette.sample(0.0).approx_eq(Color::BLACK, 1e-6));
+        assert!(palette.sample(1.0 / 6.0).approx_eq(Color::new(0.5, 0.5, 0.5), 1e-6));
+        assert!(palette.sample(0.5).approx_eq(Color::new(1.0, 0.5, 0.5), 1e-6));
+        assert!(palette.sample(1.0).approx_eq(Color::BLUE, 1e-6));
+    }
+
+    #[test]
+    fn transparent_stop_fades_alpha_between_stops() {
+        let palette = PaletteA::new(vec![ColorA::new(1.0, 0.0, 0.0, 1.0), ColorA::TRANSPARENT, ColorA::new(0.0, 0.0, 1.0, 1.0)]);
+
+        assert_eq!(palette.sample(0.0).a, 1.0);
+        assert_eq!(palette.sample(0.5).a, 0.0);
+        assert_eq!(palette.sample(1.0).a, 1.0);
+
+        for t in [0.1, 0.25, 0.4, 0.6, 0.9] {
+            let alpha = palette.sample(t).a;
+            assert!(alpha > 0.0 && alpha < 1.0, "alpha {alpha} at {t}");
+        }
+
+        assert!((palette.sample(0.25).a - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lut_is_within_one_step_of_sample() {
+        let palette = Palette::new(vec![Color::RED, Color::new(0.2, 0.9, 0.4), Color::BLUE]);
+        let n = 64;
+        let lut = palette.to_lut(n);
+
+        for k in 0..=1000 {
+            let t = k as f32 / 1000.0;
+
+            // Rounding to the nearest entry moves `t` by at most half a step, over which no channel of this palette
+            // changes by more than `0.9 / 0.5` per unit of `t`, and storing the entry as bytes adds one more level.
+            let max_error = 1.8 * 0.5 / (n - 1) as f32 + 1.0 / 255.0;
+            let Rgb([r, g, b]) = sample_lut(&lut, t);
+            let expected = palette.sample(t);
+
+            for (actual, expected) in [(r, expected.r), (g, expected.g), (b, expected.b)] {
+                assert!((actual as f32 / 255.0 - expected).abs() <= max_error, "at {t}: {actual} vs {expected}");
+            }
+        }
+
+        assert_eq!(palette.to_lut(1), vec![Rgb([255, 0, 0])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one entry")]
+    fn empty_lut_is_rejected() {
+        Palette::grayscale().to_lut(0);
+    }
+
+    #[test]
+    fn crossfade_matches_the_ends_and_blends_between() {
+        let a = Palette::new(vec![Color::BLACK, Color::WHITE]);
+        let b = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+
+        for x in [0.0, 0.2, 0.5, 0.75, 1.0] {
+            assert!(Palette::crossfade(&a, &b, 0.0).sample(x).approx_eq(a.sample(x), 1e-6), "t = 0 at {x}");
+            assert!(Palette::crossfade(&a, &b, 1.0).sample(x).approx_eq(b.sample(x), 1e-6), "t = 1 at {x}");
+
+            let halfway = Palette::crossfade(&a, &b, 0.5).sample(x);
+            assert!(halfway.approx_eq(a.sample(x).lerp(b.sample(x), 0.5), 1e-6), "t = 0.5 at {x}");
+        }
+
+        assert_eq!(Palette::crossfade(&a, &b, 0.5).colors().len(), 3);
+    }
+}
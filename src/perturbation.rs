@@ -0,0 +1,363 @@
+#![allow(unused)]
+
+use std::collections::VecDeque;
+
+use crate::compute::ComputeSettings;
+use crate::escape::BAILOUT_SQR;
+use crate::field::Field;
+use crate::util::{timed, Complex, ComplexF64, Point};
+
+/// How small `|z|^2` may get relative to `|Z|^2` before a perturbed pixel is considered glitched.
+///
+/// This is Pauldelbrot's criterion: once a pixel's orbit passes much closer to zero than the reference orbit does,
+/// the delta has lost all of its significant digits and the result can no longer be trusted.
+const GLITCH_TOLERANCE: f64 = 1e-6;
+
+/// The orbit of a single reference point, computed in `f64`, that nearby pixels are iterated relative to.
+#[derive(Debug, Clone)]
+pub struct ReferenceOrbit {
+    /// The reference point.
+    pub c: ComplexF64,
+    /// The iterates `Z_0 = 0, Z_1, Z_2, ...` of the reference point, up to `max_iter` or until it escapes.
+    pub orbit: Vec<ComplexF64>,
+}
+
+impl ReferenceOrbit {
+    /// Computes the orbit of the reference point `c`.
+    pub fn new(c: ComplexF64, max_iter: u32) -> ReferenceOrbit {
+        let mut z = Complex::new(0.0, 0.0);
+        let mut orbit = Vec::with_capacity(max_iter as usize + 1);
+        orbit.push(z);
+
+        for _ in 0..max_iter {
+            z = z * z + c;
+            orbit.push(z);
+
+            if z.norm_sqr() > BAILOUT_SQR as f64 {
+                break;
+            }
+        }
+
+        ReferenceOrbit { c, orbit }
+    }
+}
+
+/// The outcome of iterating a pixel relative to a reference orbit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PerturbedCount {
+    /// The pixel escaped with the given count.
+    Escaped(f32),
+    /// The pixel did not escape within `max_iter` iterations.
+    Interior,
+    /// The result cannot be trusted and the pixel needs a different reference orbit.
+    Glitched,
+}
+
+impl PerturbedCount {
+    /// Converts this result to an escape count, or `None` for interior and glitched pixels.
+    #[inline]
+    pub fn count(self) -> Option<f32> {
+        match self {
+            PerturbedCount::Escaped(count) => Some(count),
+            _ => None,
+        }
+    }
+}
+
+/// Iterates the point `reference.c + dc` by tracking only its difference from the reference orbit.
+///
+/// With `z_n = Z_n + d_n`, the difference follows `d_{n+1} = (2 Z_n + d_n) d_n + dc`. Since `d_n` stays small,
+/// it keeps its precision even when `dc` is far below what could be added to `reference.c` directly.
+pub fn perturbed_count(reference: &ReferenceOrbit, dc: ComplexF64, settings: &ComputeSettings) -> PerturbedCount {
+    let orbit = &reference.orbit;
+    let mut d = Complex::new(0.0, 0.0);
+
+    for i in 0..settings.max_iter as usize {
+        if i + 1 >= orbit.len() {
+            return PerturbedCount::Glitched;
+        }
+
+        d = (orbit[i] * 2.0 + d) * d + dc;
+        let z = orbit[i + 1] + d;
+        let norm_sqr = z.norm_sqr();
+
+        if norm_sqr > BAILOUT_SQR as f64 {
+            let count = if settings.smooth { settings.smooth_fraction(i as u32, z.to_f32()) } else { i as f32 };
+            return PerturbedCount::Escaped(count);
+        }
+
+        if norm_sqr < GLITCH_TOLERANCE * orbit[i + 1].norm_sqr() {
+            return PerturbedCount::Glitched;
+        }
+    }
+
+    PerturbedCount::Interior
+}
+
+/// Computes the escape count of the point `c` by iterating it directly in `f64`.
+pub fn direct_count_f64(c: ComplexF64, settings: &ComputeSettings) -> Option<f32> {
+    let mut z: ComplexF64 = Complex::new(0.0, 0.0);
+
+    for i in 0..settings.max_iter {
+        z = z * z + c;
+
+        if z.norm_sqr() > BAILOUT_SQR as f64 {
+            return Some(if settings.smooth { settings.smooth_fraction(i, z.to_f32()) } else { i as f32 });
+        }
+    }
+
+    None
+}
+
+/// The offset from the center of the image of the pixel `p`, in complex units.
+///
+/// This uses the same convention as [`Viewport`](crate::compute::Viewport), with `scale` being the half-height
+/// of the visible region, but keeps everything in `f64` so that deep zooms can be described.
+#[inline]
+pub fn pixel_offset(p: Point<u32>, size: Point<u32>, scale: f64) -> ComplexF64 {
+    let half_height = size.y as f64 / 2.0;
+    let x = (p.x as f64 - size.x as f64 / 2.0) / half_height;
+    let y = (half_height - p.y as f64) / half_height;
+
+    Complex::new(x, y) * scale
+}
+
+/// Computes the escape counts of an image centered on `center` using perturbation, correcting glitches
+/// with up to `max_references` reference orbits.
+///
+/// The first reference orbit is taken at the center of the image. After each pass, the glitched pixels are
+/// grouped into connected clusters, and a new reference is placed inside the largest one, close to its centroid.
+/// Only the pixels in that cluster are iterated again. Any pixels still glitched once the references run out
+/// are iterated directly in `f64`, so the result matches [`compute_counts_f64`] up to rounding. The two round
+/// differently, which usually only shows in the last digits of smooth counts, but can change the count of a pixel
+/// right on the boundary, where the orbit amplifies every rounding error.
+pub fn render_perturbed(
+    size: Point<u32>,
+    center: ComplexF64,
+    scale: f64,
+    settings: &ComputeSettings,
+    max_references: u32,
+) -> Field<Option<f32>> {
+    render_perturbed_with_log(size, center, scale, settings, max_references, |_| {})
+}
+
+/// Like [`render_perturbed`], but reports each phase of the render and how long it took to `log`.
+pub fn render_perturbed_with_log(
+    size: Point<u32>,
+    center: ComplexF64,
+    scale: f64,
+    settings: &ComputeSettings,
+    max_references: u32,
+    mut log: impl FnMut(&str),
+) -> Field<Option<f32>> {
+    let (offsets, results) = perturbed_passes(size, center, scale, settings, max_references, &mut log);
+
+    let counts = timed(&mut log, "iterating remaining glitched pixels directly", || {
+        results
+            .data
+            .iter()
+            .zip(&offsets)
+            .map(|(result, &offset)| match result {
+                PerturbedCount::Glitched => direct_count_f64(center + offset, settings),
+                result => result.count(),
+            })
+            .collect()
+    });
+
+    Field::new(counts, size)
+}
+
+/// Runs the reference passes of [`render_perturbed`], returning the offset of every pixel from `center`
+/// along with the results, which may still contain glitched pixels.
+fn perturbed_passes(
+    size: Point<u32>,
+    center: ComplexF64,
+    scale: f64,
+    settings: &ComputeSettings,
+    max_references: u32,
+    log: &mut impl FnMut(&str),
+) -> (Vec<ComplexF64>, Field<PerturbedCount>) {
+    let offsets: Vec<ComplexF64> = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| Point::new(x, y)))
+        .map(|p| pixel_offset(p, size, scale))
+        .collect();
+
+    let reference = timed(log, "computing reference orbit", || ReferenceOrbit::new(center, settings.max_iter));
+    let mut results = timed(log, "iterating pixels", || {
+        Field::new(offsets.iter().map(|&dc| perturbed_count(&reference, dc, settings)).collect(), size)
+    });
+
+    for _ in 1..max_references {
+        let Some(cluster) = largest_glitch_cluster(&results) else {
+            break;
+        };
+
+        let ref_index = closest_to_centroid(&cluster, size);
+        let ref_offset = offsets[ref_index];
+        let reference = timed(log, "computing reference orbit", || {
+            ReferenceOrbit::new(center + ref_offset, settings.max_iter)
+        });
+
+        timed(log, &format!("correcting {} glitched pixels", cluster.len()), || {
+            for i in cluster {
+                results.data[i] = perturbed_count(&reference, offsets[i] - ref_offset, settings);
+            }
+        });
+    }
+
+    (offsets, results)
+}
+
+/// Computes the escape counts of an image centered on `center` by iterating every pixel directly in `f64`.
+pub fn compute_counts_f64(size: Point<u32>, center: ComplexF64, scale: f64, settings: &ComputeSettings) -> Field<Option<f32>> {
+    let counts = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| Point::new(x, y)))
+        .map(|p| direct_count_f64(center + pixel_offset(p, size, scale), settings))
+        .collect();
+
+    Field::new(counts, size)
+}
+
+/// Finds the largest 4-connected cluster of glitched pixels, returned as indices into the field.
+fn largest_glitch_cluster(results: &Field<PerturbedCount>) -> Option<Vec<usize>> {
+    let size = results.size;
+    let mut visited = vec![false; results.data.len()];
+    let mut largest: Option<Vec<usize>> = None;
+
+    for start in 0..results.data.len() {
+        if visited[start] || results.data[start] != PerturbedCount::Glitched {
+            continue;
+        }
+
+        let mut cluster = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(i) = queue.pop_front() {
+            cluster.push(i);
+
+            let x = (i % size.x as usize) as u32;
+            let y = (i / size.x as usize) as u32;
+            let neighbors = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < size.x).then(|| i + 1),
+                (y > 0).then(|| i - size.x as usize),
+                (y + 1 < size.y).then(|| i + size.x as usize),
+            ];
+
+            for n in neighbors.into_iter().flatten() {
+                if !visited[n] && results.data[n] == PerturbedCount::Glitched {
+                    visited[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        if largest.as_ref().is_none_or(|l| cluster.len() > l.len()) {
+            largest = Some(cluster);
+        }
+    }
+
+    largest
+}
+
+/// Finds the pixel of `cluster` that lies closest to the cluster's centroid.
+///
+/// The centroid itself can fall outside of a cluster that is not convex, so the nearest member is used instead.
+fn closest_to_centroid(cluster: &[usize], size: Point<u32>) -> usize {
+    let to_point = |i: usize| ((i % size.x as usize) as f64, (i / size.x as usize) as f64);
+
+    let (sum_x, sum_y) = cluster.iter().fold((0.0, 0.0), |(sx, sy), &i| {
+        let (x, y) = to_point(i);
+        (sx + x, sy + y)
+    });
+    let (cx, cy) = (sum_x / cluster.len() as f64, sum_y / cluster.len() as f64);
+
+    let dist_sqr = |i: usize| {
+        let (x, y) = to_point(i);
+        (x - cx) * (x - cx) + (y - cy) * (y - cy)
+    };
+
+    *cluster
+        .iter()
+        .min_by(|&&a, &&b| dist_sqr(a).total_cmp(&dist_sqr(b)))
+        .expect("glitch clusters are never empty")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that a perturbed render matches the direct `f64` one pixel by pixel.
+    ///
+    /// Both agree on which pixels escape and on every whole count. Smooth counts are taken from slightly different
+    /// final iterates, since the two round differently, which moves their fraction by well under `0.01`.
+    fn assert_matches_direct(size: Point<u32>, center: ComplexF64, scale: f64, max_references: u32) {
+        for smooth in [false, true] {
+            let settings = ComputeSettings { max_iter: 500, smooth, ..ComputeSettings::default() };
+            let perturbed = render_perturbed(size, center, scale, &settings, max_references);
+            let direct = compute_counts_f64(size, center, scale, &settings);
+
+            for (i, (a, b)) in perturbed.data.iter().zip(&direct.data).enumerate() {
+                match (a, b) {
+                    (Some(a), Some(b)) if smooth => assert!((a - b).abs() < 0.01, "{a} and {b} at {i}"),
+                    _ => assert_eq!(a, b, "at {i}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn second_reference_corrects_a_reference_that_escapes() {
+        // The center lies just off the real axis in the gap between the cardioid and the period-2 bulb, so it escapes
+        // and its short reference orbit runs out before the many pixels around it that lie inside the set.
+        let size = Point::new(40, 30);
+        let center = Complex::new(-0.75, 0.01);
+        let scale = 0.01;
+        let settings = ComputeSettings { max_iter: 500, ..ComputeSettings::default() };
+        let glitched = |max_references| {
+            let (_, results) = perturbed_passes(size, center, scale, &settings, max_references, &mut |_| {});
+            results.data.iter().filter(|&&r| r == PerturbedCount::Glitched).count()
+        };
+
+        assert!(ReferenceOrbit::new(center, settings.max_iter).orbit.len() < settings.max_iter as usize + 1);
+        assert!(glitched(1) > 0);
+        assert_eq!(glitched(2), 0);
+
+        assert_matches_direct(size, center, scale, 2);
+        assert_matches_direct(Point::new(40, 30), Complex::new(-0.743_643_887_037_151, 0.131_825_904_205_33), 1e-6, 2);
+    }
+
+    #[test]
+    fn orbits_passing_close_to_zero_are_glitched() {
+        // The reference at `-0.5` never escapes, so it cannot run out, while the pixels at `-1` and `0`
+        // land exactly on zero after one or two iterations, far closer than the reference ever gets.
+        let size = Point::new(40, 20);
+        let center = Complex::new(-0.5, 0.0);
+        let scale = 0.5;
+        let settings = ComputeSettings { max_iter: 500, ..ComputeSettings::default() };
+
+        assert_eq!(ReferenceOrbit::new(center, settings.max_iter).orbit.len(), settings.max_iter as usize + 1);
+
+        let (offsets, results) = perturbed_passes(size, center, scale, &settings, 1, &mut |_| {});
+        let glitched: Vec<(u64, u64)> = (0..results.data.len())
+            .filter(|&i| results.data[i] == PerturbedCount::Glitched)
+            .map(|i| (center + offsets[i]).canonical_bits())
+            .collect();
+        assert_eq!(glitched, [ComplexF64::new(-1.0, 0.0).canonical_bits(), ComplexF64::new(0.0, 0.0).canonical_bits()]);
+
+        assert_matches_direct(size, center, scale, 2);
+    }
+
+    #[test]
+    fn log_receives_each_phase() {
+        let mut messages = Vec::new();
+        let settings = ComputeSettings { max_iter: 200, ..ComputeSettings::default() };
+        render_perturbed_with_log(Point::new(8, 6), Complex::new(-0.75, 0.01), 0.01, &settings, 2, |m| messages.push(m.to_owned()));
+
+        let phases: Vec<&str> = messages.iter().filter(|m| !m.contains(" took ")).map(String::as_str).collect();
+        assert_eq!(phases[..2], ["computing reference orbit", "iterating pixels"]);
+        assert_eq!(phases.last(), Some(&"iterating remaining glitched pixels directly"));
+        assert_eq!(messages.len(), 2 * phases.len());
+    }
+}
@@ -0,0 +1,1217 @@
+#![allow(unused)]
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use image::{ImageResult, Rgb, RgbaImage};
+
+use crate::compute::{block_counts, compute_counts, iterated_count, point_count, row_is_interior, ComputeSettings, Viewport};
+use crate::escape::{distance_estimate, escape_time, final_iterate, find_period, in_main_cardioid, in_period2_bulb};
+use crate::field::Field;
+use crate::palette::{repeat, sample_lut, DualPalette, Palette, PaletteA};
+use crate::util::{timed, Color, ColorA, Complex, ComplexF32, MandelbrotImage, MandelbrotImage16, Point};
+
+/// How much the palette advances for each increase in the detected period.
+const PERIOD_PALETTE_STEP: f32 = 0.125;
+
+/// How points inside the set are colored.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteriorColoring {
+    /// Every interior point gets the same color.
+    Solid(Color),
+    /// Interior points are colored by the period of the cycle their orbit settles into,
+    /// which makes each bulb of the set stand out.
+    /// Points whose period could not be found get the `fallback` color.
+    Period { palette: Palette, fallback: Color },
+}
+
+impl InteriorColoring {
+    /// Picks the color of the interior point `c`.
+    fn color(&self, c: ComplexF32, max_iter: u32) -> Color {
+        match self {
+            InteriorColoring::Solid(color) => *color,
+            InteriorColoring::Period { palette, fallback } => match find_period(c, max_iter) {
+                Some(period) => palette.sample_cyclic((period - 1) as f32 * PERIOD_PALETTE_STEP),
+                None => *fallback,
+            },
+        }
+    }
+}
+
+/// What places a point inside the set on the interior palette of a [`DualPalette`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteriorMetric {
+    /// The period of the cycle the orbit settles into, stepping through the palette like [`InteriorColoring::Period`].
+    /// Points whose period could not be found get the first color of the palette.
+    #[default]
+    Period,
+    /// The argument of the last iterate, after `max_iter` iterations, with a full turn spanning the palette once.
+    FinalAngle,
+}
+
+impl InteriorMetric {
+    /// Where the interior point `c` falls on the interior palette, which is sampled cyclically.
+    fn position(self, c: ComplexF32, max_iter: u32) -> f32 {
+        match self {
+            InteriorMetric::Period => find_period(c, max_iter).map_or(0.0, |period| (period - 1) as f32 * PERIOD_PALETTE_STEP),
+            InteriorMetric::FinalAngle => {
+                let mut z = Complex::new(0.0, 0.0);
+                for _ in 0..max_iter {
+                    z = z * z + c;
+                }
+
+                z.arg() / std::f32::consts::TAU
+            }
+        }
+    }
+}
+
+/// How points outside the set are colored.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExteriorColoring {
+    /// Exterior points are looked up in the palette by their escape count.
+    #[default]
+    Palette,
+    /// Exterior points alternate between two colors with each whole iteration of their escape count,
+    /// splitting the outside of the set into bands. Smooth counts are rounded down first.
+    IterationParity { even: Color, odd: Color },
+}
+
+impl ExteriorColoring {
+    /// Picks the color of an exterior point from its escape count, or `None` if it is looked up in the palette.
+    #[inline]
+    fn color(self, count: f32) -> Option<Color> {
+        match self {
+            ExteriorColoring::Palette => None,
+            ExteriorColoring::IterationParity { even, odd } => {
+                Some(if (count.floor() as i64).rem_euclid(2) == 0 { even } else { odd })
+            }
+        }
+    }
+}
+
+/// How escape counts are mapped to the range `[0, 1]` before they are looked up in the palette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalizeMode {
+    /// Divide counts by `max_iter`, so the palette always spans every possible count.
+    /// Every count gets the last color of the palette if `max_iter` is not above `min_iter`.
+    ///
+    /// Changing `max_iter` changes the color of every pixel, which makes colors jump between the frames of
+    /// an animation that raises `max_iter` as it zooms.
+    MaxIter,
+    /// Divide counts by a fixed reference count, so a pixel keeps its color no matter what `max_iter` is.
+    /// Counts above the reference all get the last color of the palette, and so does every count
+    /// if the reference is not above `min_iter`.
+    Fixed(f32),
+}
+
+impl NormalizeMode {
+    /// Maps `count` to the range `[0, 1]`, with `min_iter` mapping to zero.
+    #[inline]
+    pub fn normalize(self, count: f32, min_iter: u32, max_iter: u32) -> f32 {
+        let min_iter = min_iter as f32;
+
+        match self {
+            NormalizeMode::MaxIter if max_iter as f32 <= min_iter => 1.0,
+            NormalizeMode::MaxIter => (count - min_iter) / (max_iter as f32 - min_iter),
+            NormalizeMode::Fixed(reference) if reference <= min_iter => 1.0,
+            NormalizeMode::Fixed(reference) => ((count - min_iter) / (reference - min_iter)).min(1.0),
+        }
+    }
+}
+
+/// Settings that control how an image is rendered.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderSettings {
+    /// Settings that control how escape counts are computed.
+    pub compute: ComputeSettings,
+    /// How escape counts are mapped onto the palette.
+    pub normalize_mode: NormalizeMode,
+    /// How many times the palette repeats over the range of escape counts.
+    #[cfg_attr(feature = "serde", serde(default = "default_cycles"))]
+    pub cycles: f32,
+    /// The escape count at which the palette starts.
+    ///
+    /// Far away from the set, every point escapes within the first few iterations, which wastes part of the palette
+    /// on a uniform fringe. Counts below `min_iter` are clamped to it, so the palette is spread over the more
+    /// interesting counts near the boundary instead.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_iter: u32,
+    /// The color of points that escape before `min_iter`. If `None`, they are colored as if they escaped at `min_iter`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fringe_color: Option<Color>,
+    /// How points outside the set are colored, for those that escape after `min_iter`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub exterior: ExteriorColoring,
+    /// How points inside the set are colored.
+    pub interior: InteriorColoring,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            compute: ComputeSettings::default(),
+            normalize_mode: NormalizeMode::MaxIter,
+            cycles: 1.0,
+            min_iter: 0,
+            fringe_color: None,
+            exterior: ExteriorColoring::Palette,
+            interior: InteriorColoring::Solid(Color::BLACK),
+        }
+    }
+}
+
+/// The value of [`RenderSettings::cycles`] for settings saved before it existed.
+#[inline]
+fn default_cycles() -> f32 {
+    1.0
+}
+
+/// Colors a single point, using `palette` for points outside the set.
+#[inline]
+pub fn color_point(c: ComplexF32, settings: &RenderSettings, palette: &Palette) -> Color {
+    color_count(c, point_count(c, &settings.compute), settings, palette)
+}
+
+/// Colors the point `c` given its escape count, using `palette` for points outside the set.
+pub fn color_count(c: ComplexF32, count: Option<f32>, settings: &RenderSettings, palette: &Palette) -> Color {
+    let max_iter = settings.compute.max_iter;
+
+    match count {
+        Some(count) => match palette_position(count, settings) {
+            Some(t) => settings.exterior.color(count).unwrap_or_else(|| palette.sample(t)),
+            None => settings.fringe_color.unwrap_or_else(|| palette.sample(0.0)),
+        },
+        None => settings.interior.color(c, max_iter),
+    }
+}
+
+/// Where the escape count `count` falls within the palette, after normalization and repetition.
+///
+/// Returns `None` for counts below `settings.min_iter`, which get the fringe color instead.
+#[inline]
+pub fn palette_position(count: f32, settings: &RenderSettings) -> Option<f32> {
+    if count < settings.min_iter as f32 {
+        return None;
+    }
+
+    let t = settings.normalize_mode.normalize(count, settings.min_iter, settings.compute.max_iter);
+    Some(repeat(t, settings.cycles))
+}
+
+/// Like [`colorize`], but looks exterior colors up in a table created by [`Palette::to_lut`]
+/// instead of interpolating the palette for every pixel.
+pub fn colorize_lut(counts: &Field<Option<f32>>, viewport: &Viewport, settings: &RenderSettings, lut: &[Rgb<u8>]) -> MandelbrotImage {
+    let size = counts.size;
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let p = Point::new(x, y);
+
+            let color = match counts.data[counts.index(p)] {
+                Some(count) => match (palette_position(count, settings), settings.fringe_color) {
+                    (Some(t), _) => settings.exterior.color(count).map_or_else(|| sample_lut(lut, t), Rgb::from),
+                    (None, Some(fringe)) => fringe.into(),
+                    (None, None) => lut[0],
+                },
+                None => settings.interior.color(viewport.pixel_to_complex(p, size), settings.compute.max_iter).into(),
+            };
+
+            im.put_pixel(x, y, color);
+        }
+    }
+
+    im
+}
+
+/// Colors a field of escape counts, such as the one returned by [`compute_counts`](crate::compute::compute_counts).
+///
+/// `viewport` must be the one the counts were computed with, since some interior colorings
+/// need to know which point each pixel represents.
+pub fn colorize(counts: &Field<Option<f32>>, viewport: &Viewport, settings: &RenderSettings, palette: &Palette) -> Field<Color> {
+    let size = counts.size;
+    let mut colors = Vec::with_capacity(counts.data.len());
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let p = Point::new(x, y);
+            let c = viewport.pixel_to_complex(p, size);
+            colors.push(color_count(c, counts.data[counts.index(p)], settings, palette));
+        }
+    }
+
+    Field::new(colors, size)
+}
+
+/// Like [`colorize`], but colors the interior from the interior palette of `palettes`, placed by `metric`,
+/// instead of with `settings.interior`.
+///
+/// Exterior points are colored from the exterior palette exactly as [`colorize`] would color them.
+pub fn colorize_dual(
+    counts: &Field<Option<f32>>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palettes: &DualPalette,
+    metric: InteriorMetric,
+) -> Field<Color> {
+    let size = counts.size;
+    let mut colors = Vec::with_capacity(counts.data.len());
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let p = Point::new(x, y);
+            let c = viewport.pixel_to_complex(p, size);
+
+            colors.push(match counts.data[counts.index(p)] {
+                Some(count) => color_count(c, Some(count), settings, &palettes.exterior),
+                None => palettes.interior.sample_cyclic(metric.position(c, settings.compute.max_iter)),
+            });
+        }
+    }
+
+    Field::new(colors, size)
+}
+
+/// The number of bits per channel of a saved image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BitDepth {
+    /// 8 bits per channel, which is what most viewers expect.
+    #[default]
+    Eight,
+    /// 16 bits per channel, which keeps smooth gradients from breaking up into visible bands.
+    Sixteen,
+}
+
+/// Saves a field of colors, such as the one returned by [`colorize`], to `path` with `depth` bits per channel.
+///
+/// The format is picked from the extension of `path`. It has to support the chosen depth, which for
+/// [`BitDepth::Sixteen`] means a format like PNG or TIFF.
+pub fn save_colors(colors: &Field<Color>, depth: BitDepth, path: &str) -> ImageResult<()> {
+    let size = colors.size;
+
+    match depth {
+        BitDepth::Eight => MandelbrotImage::from_fn(size.x, size.y, |x, y| colors.data[colors.index(Point::new(x, y))].into()).save(path),
+        BitDepth::Sixteen => MandelbrotImage16::from_fn(size.x, size.y, |x, y| colors.data[colors.index(Point::new(x, y))].into()).save(path),
+    }
+}
+
+/// Renders the Mandelbrot set into a new image of the given size.
+///
+/// This is the fast path: the Mandelbrot step is written out directly, so the compiler can inline it and
+/// it can use the Mandelbrot-specific [`InteriorEarlyOut`](crate::escape::InteriorEarlyOut).
+/// Use [`render_iterated`] to render other formulas.
+pub fn render(size: Point<u32>, viewport: &Viewport, settings: &RenderSettings, palette: &Palette) -> MandelbrotImage {
+    render_tile(Point::new(0, 0), size, size, viewport, settings, palette)
+}
+
+/// Like [`render`], but computes all escape counts before colorizing them,
+/// reporting each phase and how long it took to `log`.
+pub fn render_with_log(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    mut log: impl FnMut(&str),
+) -> MandelbrotImage {
+    let counts = timed(&mut log, "computing counts", || compute_counts(size, viewport, &settings.compute));
+    let colors = timed(&mut log, "colorizing", || colorize(&counts, viewport, settings, palette));
+
+    let mut im = MandelbrotImage::new(size.x, size.y);
+    for (pixel, color) in im.pixels_mut().zip(colors.data) {
+        *pixel = color.into();
+    }
+
+    im
+}
+
+/// Renders the Mandelbrot set as text for a terminal with 24-bit color, `size.x` characters wide.
+///
+/// Each character is an upper half block, with its foreground colored by one pixel and its background
+/// by the pixel below it, so the `size.y` pixel rows take up half as many lines of text. Every line
+/// ends by resetting the colors, so the terminal is left as it was.
+pub fn render_ansi(size: Point<u32>, viewport: &Viewport, settings: &RenderSettings, palette: &Palette) -> String {
+    let colors = colorize(&compute_counts(size, viewport, &settings.compute), viewport, settings, palette);
+    let mut out = String::new();
+
+    for y in (0..size.y).step_by(2) {
+        for x in 0..size.x {
+            out += &colors.data[colors.index(Point::new(x, y))].to_ansi_fg();
+
+            if let Some(&below) = colors.get(Point::new(x, y + 1)) {
+                out += &below.to_ansi_bg();
+            }
+
+            out.push('▀');
+        }
+
+        out += "\x1b[0m\n";
+    }
+
+    out
+}
+
+/// Renders the fractal given by iterating `z = step(z, c)` from `z = 0` into a new image of the given size.
+///
+/// `step` is a generic parameter rather than a `dyn Fn`, so each closure gets its own copy of the render loop
+/// with the step inlined into it. Rendering the Mandelbrot set this way gives the same image as [`render`],
+/// but without its shortcuts for the inside of the set, which only work for the built-in step.
+pub fn render_iterated<F: Fn(ComplexF32, ComplexF32) -> ComplexF32>(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    step: F,
+) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let c = viewport.pixel_to_complex(Point::new(x, y), size);
+            let count = iterated_count(c, &settings.compute, &step);
+
+            im.put_pixel(x, y, color_count(c, count, settings, palette).into());
+        }
+    }
+
+    im
+}
+
+/// Renders the Mandelbrot set with the exterior domain colored by the first iterate past the bailout.
+///
+/// The hue of each exterior pixel follows the argument of that iterate, see [`domain_color`], which splits
+/// the outside of the set into the familiar phase portrait. Interior points are colored by `settings.interior`,
+/// and the palette and exterior settings are not used.
+pub fn render_domain_colored(size: Point<u32>, viewport: &Viewport, settings: &RenderSettings) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(size.x, size.y);
+    let max_iter = settings.compute.max_iter;
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let c = viewport.pixel_to_complex(Point::new(x, y), size);
+            let color = match final_iterate(c, max_iter) {
+                Some((_, z)) => domain_color(z),
+                None => settings.interior.color(c, max_iter),
+            };
+
+            im.put_pixel(x, y, color.into());
+        }
+    }
+
+    im
+}
+
+/// Colors the complex value `z` by its phase and magnitude, as in a domain coloring plot.
+///
+/// The hue goes once around the color wheel with the argument of `z`, starting from red on the positive real axis,
+/// so opposite phases get complementary hues. The brightness rises from half to full between each power of two of `|z|`,
+/// which draws rings at `|z| = 1, 2, 4, ...`.
+#[inline]
+pub fn domain_color(z: ComplexF32) -> Color {
+    let hue = (z.arg() / std::f32::consts::TAU).rem_euclid(1.0);
+    let brightness = 0.5 + 0.5 * z.abs().log2().rem_euclid(1.0);
+
+    Color::from_hsv([hue, 1.0, brightness])
+}
+
+/// Renders a `tile_size` piece of a larger image of size `total_size`, starting at the pixel `origin`.
+///
+/// Rows of the tile that lie entirely inside the set's largest components are given the interior color
+/// without being iterated, see [`row_is_interior`].
+pub fn render_tile(
+    origin: Point<u32>,
+    tile_size: Point<u32>,
+    total_size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(tile_size.x, tile_size.y);
+
+    for y in 0..tile_size.y {
+        let interior = row_is_interior(viewport, total_size, origin.y + y, origin.x, origin.x + tile_size.x);
+
+        for x in 0..tile_size.x {
+            let c = viewport.pixel_to_complex(Point::new(origin.x + x, origin.y + y), total_size);
+            let color = if interior {
+                color_count(c, None, settings, palette)
+            } else {
+                color_point(c, settings, palette)
+            };
+
+            im.put_pixel(x, y, color.into());
+        }
+    }
+
+    im
+}
+
+/// Like [`render`], but computes the image in 2x2 blocks of pixels, see [`block_counts`].
+///
+/// Without smooth coloring, the four pixels of a block get the same exterior color when they escape at the same
+/// iteration, so it is only computed once. Smooth coloring shares no work between the pixels: each of them has its
+/// own fraction, and with it its own color, so the only change from [`render`] is that the block is iterated in
+/// lockstep. The result is identical to [`render`] either way.
+pub fn render_blocked(size: Point<u32>, viewport: &Viewport, settings: &RenderSettings, palette: &Palette) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for block_y in (0..size.y).step_by(2) {
+        for block_x in (0..size.x).step_by(2) {
+            // Blocks along the right and bottom edges repeat their last pixel to fill up the missing ones.
+            let pixels = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| Point::new((block_x + dx).min(size.x - 1), (block_y + dy).min(size.y - 1)));
+            let c = pixels.map(|p| viewport.pixel_to_complex(p, size));
+            let counts = block_counts(c, &settings.compute);
+
+            let shared = match counts[0] {
+                Some(count) if counts.iter().all(|&n| n == Some(count)) => Some(color_count(c[0], counts[0], settings, palette)),
+                _ => None,
+            };
+
+            for k in 0..4 {
+                let color = shared.unwrap_or_else(|| color_count(c[k], counts[k], settings, palette));
+                im.put_pixel(pixels[k].x, pixels[k].y, color.into());
+            }
+        }
+    }
+
+    im
+}
+
+/// Renders an image of size `total_size` straight to a PNG file at `path`, one `tile_size` tile at a time.
+///
+/// Only a single row of tiles is kept in memory at once, so this can produce images far larger than
+/// would fit in memory with [`render`]. A `tile_size` of `0` is rejected with an
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) error before anything is written.
+pub fn render_tiled_to_file(
+    total_size: Point<u32>,
+    tile_size: u32,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    path: &str,
+) -> Result<(), png::EncodingError> {
+    render_tiled_to_file_with_log(total_size, tile_size, viewport, settings, palette, path, |_| {})
+}
+
+/// Like [`render_tiled_to_file`], but reports progress and how long each phase took to `log`.
+pub fn render_tiled_to_file_with_log(
+    total_size: Point<u32>,
+    tile_size: u32,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    path: &str,
+    mut log: impl FnMut(&str),
+) -> Result<(), png::EncodingError> {
+    if tile_size == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "tile size must be at least 1").into());
+    }
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, total_size.x, total_size.y);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    let row_len = total_size.x as usize * 3;
+
+    timed(&mut log, "rendering tiles", || {
+        for band_y in (0..total_size.y).step_by(tile_size as usize) {
+            let band_height = tile_size.min(total_size.y - band_y);
+            let mut band = vec![0; row_len * band_height as usize];
+
+            for tile_x in (0..total_size.x).step_by(tile_size as usize) {
+                let tile_width = tile_size.min(total_size.x - tile_x);
+                let tile = render_tile(
+                    Point::new(tile_x, band_y),
+                    Point::new(tile_width, band_height),
+                    total_size,
+                    viewport,
+                    settings,
+                    palette,
+                );
+
+                let tile_row_len = tile_width as usize * 3;
+                for (y, row) in tile.chunks_exact(tile_row_len).enumerate() {
+                    let start = y * row_len + tile_x as usize * 3;
+                    band[start..start + tile_row_len].copy_from_slice(row);
+                }
+            }
+
+            stream.write_all(&band)?;
+        }
+
+        Ok::<_, png::EncodingError>(())
+    })?;
+
+    timed(&mut log, "finishing file", || stream.finish())
+}
+
+/// Saves a looping animated PNG (APNG) to `path` that cycles `palette` through `frames` frames,
+/// each shown for `delay_ms` milliseconds, without computing the counts again.
+///
+/// Every frame shifts the palette by `1 / frames` of its length, so after the last frame it is back where it started.
+/// The palette wraps around while cycling, so for a seamless loop it should end on the same color it starts with.
+/// Interior and fringe pixels keep their color throughout. `viewport` must be the one the counts were computed with,
+/// as for [`colorize`].
+pub fn save_palette_cycle_apng(
+    counts: &Field<Option<f32>>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    frames: u32,
+    delay_ms: u16,
+    path: &str,
+) -> Result<(), png::EncodingError> {
+    let size = counts.size;
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, size.x, size.y);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames, 0)?;
+    encoder.set_frame_delay(delay_ms, 1000)?;
+
+    let mut writer = encoder.write_header()?;
+    let mut data = Vec::with_capacity(counts.data.len() * 3);
+
+    for frame in 0..frames {
+        let offset = frame as f32 / frames as f32;
+        data.clear();
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let p = Point::new(x, y);
+                let count = counts.data[counts.index(p)];
+
+                let color = match count.and_then(|count| Some((count, palette_position(count, settings)?))) {
+                    Some((count, t)) => settings.exterior.color(count).unwrap_or_else(|| palette.sample_cyclic(t + offset)),
+                    None => color_count(viewport.pixel_to_complex(p, size), count, settings, palette),
+                };
+
+                data.extend_from_slice(&Rgb::<u8>::from(color).0);
+            }
+        }
+
+        writer.write_image_data(&data)?;
+    }
+
+    writer.finish()
+}
+
+/// Colors the pixel `p` with adaptive supersampling, returning its color and the number of samples taken.
+///
+/// The four corners of the pixel are sampled first. If they all agree to within `threshold` on every channel,
+/// the pixel is considered flat and their average is used. Otherwise the pixel is sampled again on an evenly
+/// spaced grid of up to `max_samples` points, which concentrates the work along the boundary of the set.
+///
+/// The grid is the largest square that fits into `max_samples`, with its outermost points on the corners.
+/// Anything smaller than `3 x 3` adds no samples between the corners, so with `max_samples` below `9`
+/// no pixel is ever subdivided.
+pub fn adaptive_pixel(
+    p: Point<u32>,
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    threshold: f32,
+    max_samples: u32,
+) -> (Color, u32) {
+    adaptive_samples(
+        p,
+        size,
+        viewport,
+        threshold,
+        max_samples,
+        |c| color_point(c, settings, palette),
+        color_difference,
+        average,
+    )
+}
+
+/// Like [`adaptive_pixel`], but colors exterior points with a palette that can be partially transparent.
+///
+/// The samples are averaged with premultiplied alpha, see [`ColorA::average`], so the pixels along the edge of
+/// a transparent region keep the color of their opaque samples rather than fading towards black.
+pub fn adaptive_pixel_rgba(
+    p: Point<u32>,
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &PaletteA,
+    threshold: f32,
+    max_samples: u32,
+) -> (ColorA, u32) {
+    adaptive_samples(
+        p,
+        size,
+        viewport,
+        threshold,
+        max_samples,
+        |c| color_point_rgba(c, settings, palette),
+        |a, b| color_difference(a.color(), b.color()).max((a.a - b.a).abs()),
+        ColorA::average,
+    )
+}
+
+/// Renders the Mandelbrot set with adaptive supersampling, see [`adaptive_pixel`].
+pub fn render_adaptive_aa(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    threshold: f32,
+    max_samples: u32,
+) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let (color, _) = adaptive_pixel(Point::new(x, y), size, viewport, settings, palette, threshold, max_samples);
+            im.put_pixel(x, y, color.into());
+        }
+    }
+
+    im
+}
+
+/// Renders the Mandelbrot set with transparency and adaptive supersampling, see [`adaptive_pixel_rgba`].
+pub fn render_adaptive_aa_rgba(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &PaletteA,
+    threshold: f32,
+    max_samples: u32,
+) -> RgbaImage {
+    let mut im = RgbaImage::new(size.x, size.y);
+
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let (color, _) = adaptive_pixel_rgba(Point::new(x, y), size, viewport, settings, palette, threshold, max_samples);
+            im.put_pixel(x, y, color.into());
+        }
+    }
+
+    im
+}
+
+/// Colors the pixel `p` with a number of samples that grows as the pixel gets closer to the boundary of the set,
+/// returning its color and the number of samples taken.
+///
+/// The distance to the boundary is estimated at the center of the pixel with [`distance_estimate`] and measured
+/// in pixels. Pixels at least `de_threshold` pixels away take a single sample at their center, and closer ones
+/// take up to `max_samples` on an evenly spaced grid, with the most right on the boundary. The estimate only
+/// exists outside of the set, so a pixel whose center is inside takes a single sample when its four corners are
+/// inside as well, and is sampled as if it were on the boundary otherwise.
+pub fn adaptive_de_pixel(
+    p: Point<u32>,
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    de_threshold: f32,
+    max_samples: u32,
+) -> (Color, u32) {
+    let sample = |dx: f32, dy: f32| {
+        color_point(viewport.subpixel_to_complex(Point::new(p.x as f32 + dx, p.y as f32 + dy), size), settings, palette)
+    };
+
+    let center = viewport.subpixel_to_complex(Point::new(p.x as f32 + 0.5, p.y as f32 + 0.5), size);
+    let pixel_size = viewport.visible_height() / size.y as f32;
+    let max_iter = settings.compute.max_iter;
+    let inside = |c: ComplexF32| in_main_cardioid(c) || in_period2_bulb(c) || escape_time(c, max_iter).is_none();
+    let distance = match distance_estimate(center, max_iter) {
+        Some(de) => de / pixel_size,
+        None if [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .all(|&(dx, dy)| inside(viewport.subpixel_to_complex(Point::new(p.x as f32 + dx, p.y as f32 + dy), size))) =>
+        {
+            f32::INFINITY
+        }
+        None => 0.0,
+    };
+
+    let closeness = (1.0 - distance / de_threshold).clamp(0.0, 1.0);
+    let n = ((max_samples as f32 * closeness).sqrt() as u32).max(1);
+
+    if n == 1 {
+        return (sample(0.5, 0.5), 1);
+    }
+
+    let step = 1.0 / n as f32;
+    let samples: Vec<Color> = (0..n * n)
+        .map(|i| sample(((i % n) as f32 + 0.5) * step, ((i / n) as f32 + 0.5) * step))
+        .collect();
+
+    (average(&samples), n * n)
+}
+
+/// Renders the Mandelbrot set with supersampling concentrated along the boundary, see [`adaptive_de_pixel`].
+pub fn adaptive_de_aa(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    de_threshold: f32,
+    max_samples: u32,
+) -> MandelbrotImage {
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let (color, _) = adaptive_de_pixel(Point::new(x, y), size, viewport, settings, palette, de_threshold, max_samples);
+            im.put_pixel(x, y, color.into());
+        }
+    }
+
+    im
+}
+
+/// Colors a single point like [`color_point`], but looks exterior points up in a palette with opacity.
+///
+/// The fringe, interior and iteration parity colors have no opacity of their own, so they are always opaque.
+pub fn color_point_rgba(c: ComplexF32, settings: &RenderSettings, palette: &PaletteA) -> ColorA {
+    match point_count(c, &settings.compute) {
+        Some(count) => match palette_position(count, settings) {
+            Some(t) => settings.exterior.color(count).map_or_else(|| palette.sample(t), ColorA::from),
+            None => settings.fringe_color.map_or_else(|| palette.sample(0.0), ColorA::from),
+        },
+        None => settings.interior.color(c, settings.compute.max_iter).into(),
+    }
+}
+
+/// The sampling strategy shared by [`adaptive_pixel`] and [`adaptive_pixel_rgba`], for any kind of color.
+#[allow(clippy::too_many_arguments)]
+fn adaptive_samples<T: Copy>(
+    p: Point<u32>,
+    size: Point<u32>,
+    viewport: &Viewport,
+    threshold: f32,
+    max_samples: u32,
+    color_at: impl Fn(ComplexF32) -> T,
+    difference: impl Fn(T, T) -> f32,
+    average: impl Fn(&[T]) -> T,
+) -> (T, u32) {
+    let sample = |dx: f32, dy: f32| color_at(viewport.subpixel_to_complex(Point::new(p.x as f32 + dx, p.y as f32 + dy), size));
+
+    let corners = [sample(0.0, 0.0), sample(1.0, 0.0), sample(0.0, 1.0), sample(1.0, 1.0)];
+    let is_flat = corners.iter().all(|&c| difference(c, corners[0]) <= threshold);
+    let n = (max_samples as f32).sqrt() as u32;
+
+    if is_flat || n <= 2 {
+        return (average(&corners), corners.len() as u32);
+    }
+
+    let step = 1.0 / (n - 1) as f32;
+    let samples: Vec<T> = (0..n * n).map(|i| sample((i % n) as f32 * step, (i / n) as f32 * step)).collect();
+
+    (average(&samples), n * n)
+}
+
+/// The largest difference between any channel of two colors.
+#[inline]
+fn color_difference(a: Color, b: Color) -> f32 {
+    (a.r - b.r).abs().max((a.g - b.g).abs()).max((a.b - b.b).abs())
+}
+
+/// The average of a list of colors.
+#[inline]
+fn average(colors: &[Color]) -> Color {
+    colors.iter().fold(Color::BLACK, |acc, &c| acc + c) * (1.0 / colors.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_coloring_separates_cardioid_and_bulb() {
+        let interior = InteriorColoring::Period {
+            palette: Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]),
+            fallback: Color::WHITE,
+        };
+
+        let cardioid = interior.color(Complex::new(0.0, 0.0), 1000);
+        let bulb = interior.color(Complex::new(-1.0, 0.0), 1000);
+
+        assert!(!cardioid.approx_eq(bulb, 1e-3), "{cardioid:?} and {bulb:?} should differ");
+        assert!(!cardioid.approx_eq(Color::WHITE, 1e-3));
+        assert!(!bulb.approx_eq(Color::WHITE, 1e-3));
+    }
+
+    #[test]
+    fn tiled_render_matches_render() {
+        let size = Point::new(37, 23);
+        let viewport = Viewport::default();
+        let settings = RenderSettings::default();
+        let palette = Palette::grayscale();
+
+        let path = std::env::temp_dir().join("mandelbrot_test_tiled_render.png");
+        let path = path.to_str().unwrap();
+        render_tiled_to_file(size, 8, &viewport, &settings, &palette, path).unwrap();
+
+        let tiled = image::open(path).unwrap().into_rgb8();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(tiled, render(size, &viewport, &settings, &palette));
+    }
+
+    #[test]
+    fn tiled_render_rejects_zero_tile_size() {
+        let path = std::env::temp_dir().join("mandelbrot_test_tiled_zero.png");
+        let result = render_tiled_to_file(Point::new(4, 4), 0, &Viewport::default(), &RenderSettings::default(), &Palette::grayscale(), path.to_str().unwrap());
+
+        assert!(matches!(result, Err(png::EncodingError::IoError(e)) if e.kind() == io::ErrorKind::InvalidInput));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn fixed_normalization_keeps_colors_across_max_iter() {
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+        let c = Complex::new(0.3, 0.5);
+        let mut colors = Vec::new();
+
+        for max_iter in [100, 500, 5000] {
+            let mut settings = RenderSettings { normalize_mode: NormalizeMode::Fixed(64.0), ..RenderSettings::default() };
+            settings.compute.max_iter = max_iter;
+            colors.push(color_point(c, &settings, &palette));
+        }
+
+        assert!(colors.iter().all(|color| color.approx_eq(colors[0], 1e-6)), "{colors:?}");
+    }
+
+    #[test]
+    fn fixed_normalization_at_or_below_min_iter_gives_the_last_color() {
+        for reference in [10.0, 4.0, -3.0] {
+            let t = NormalizeMode::Fixed(reference).normalize(12.0, 10, 256);
+            assert_eq!(t, 1.0, "reference {reference}");
+        }
+    }
+
+    #[test]
+    fn two_cycles_repeat_the_palette_twice() {
+        let mut settings = RenderSettings { cycles: 2.0, ..RenderSettings::default() };
+        settings.compute.max_iter = 100;
+
+        for (count, expected) in [(0.0, 0.0), (25.0, 0.5), (50.0, 1.0), (75.0, 0.5), (100.0, 1.0)] {
+            let t = palette_position(count, &settings).unwrap();
+            assert!((t - expected).abs() < 1e-6, "count {count} gave {t}, expected {expected}");
+        }
+
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+        let z = Complex::new(0.0, 0.0);
+        assert!(color_count(z, Some(20.0), &settings, &palette).approx_eq(color_count(z, Some(70.0), &settings, &palette), 1e-6));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn settings_saved_before_newer_fields_load_with_their_defaults() {
+        let json = r#"{
+            "compute": { "max_iter": 500, "escape_norm": "Euclidean", "early_out": null, "smooth": true },
+            "normalize_mode": "MaxIter",
+            "interior": { "Solid": { "r": 0.0, "g": 0.0, "b": 0.0 } }
+        }"#;
+
+        let settings: RenderSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.cycles, 1.0);
+        assert_eq!(settings.min_iter, 0);
+        assert!(settings.fringe_color.is_none());
+        assert!(matches!(settings.exterior, ExteriorColoring::Palette));
+        assert_eq!(settings.compute.max_iter, 500);
+
+        let viewport: Viewport = serde_json::from_str(r#"{ "center": { "re": -0.5, "im": 0.0 }, "scale": 1.25 }"#).unwrap();
+        assert_eq!(viewport.stretch_x, 1.0);
+        assert_eq!(viewport.scale_x(), 1.25);
+    }
+
+    #[test]
+    fn adaptive_sampling_subdivides_only_boundary_pixels() {
+        let size = Point::new(16, 12);
+        let viewport = Viewport::default();
+        let settings = RenderSettings::default();
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+        let mut sample_counts = Vec::new();
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let p = Point::new(x, y);
+                let (color, samples) = adaptive_pixel(p, size, &viewport, &settings, &palette, 0.05, 16);
+                sample_counts.push(samples);
+
+                if samples == 4 {
+                    let center = viewport.subpixel_to_complex(Point::new(x as f32 + 0.5, y as f32 + 0.5), size);
+                    assert!(color.approx_eq(color_point(center, &settings, &palette), 0.05), "flat pixel {p:?}");
+                }
+
+                let (_, samples) = adaptive_pixel(p, size, &viewport, &settings, &palette, 0.05, 8);
+                assert_eq!(samples, 4, "below 9 samples nothing is subdivided");
+            }
+        }
+
+        assert!(sample_counts.contains(&4));
+        assert!(sample_counts.contains(&16));
+        assert!(sample_counts.iter().all(|&n| n == 4 || n == 16));
+    }
+
+    #[test]
+    fn counts_below_min_iter_get_the_fringe_color_or_clamp() {
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+        let z = Complex::new(0.0, 0.0);
+        let mut settings = RenderSettings { min_iter: 10, fringe_color: Some(Color::WHITE), ..RenderSettings::default() };
+        settings.compute.max_iter = 110;
+
+        assert!(color_count(z, Some(3.0), &settings, &palette).approx_eq(Color::WHITE, 1e-6));
+        assert!(color_count(z, Some(60.0), &settings, &palette).approx_eq(Color::GREEN, 1e-6));
+
+        settings.fringe_color = None;
+        assert!(color_count(z, Some(3.0), &settings, &palette).approx_eq(Color::RED, 1e-6));
+        assert!(color_count(z, Some(10.0), &settings, &palette).approx_eq(Color::RED, 1e-6));
+        assert!(color_count(z, Some(110.0), &settings, &palette).approx_eq(Color::BLUE, 1e-6));
+    }
+
+    #[test]
+    fn render_iterated_with_the_mandelbrot_step_matches_render() {
+        let size = Point::new(48, 32);
+        let viewport = Viewport::default();
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]);
+
+        for smooth in [false, true] {
+            let mut settings = RenderSettings::default();
+            settings.compute.max_iter = 200;
+            settings.compute.smooth = smooth;
+
+            let expected = render(size, &viewport, &settings, &palette);
+            assert_eq!(render_iterated(size, &viewport, &settings, &palette, |z, c| z * z + c), expected, "smooth: {smooth}");
+        }
+    }
+
+    #[test]
+    fn log_receives_each_phase() {
+        let mut messages = Vec::new();
+        let settings = RenderSettings::default();
+        let im = render_with_log(Point::new(8, 6), &Viewport::default(), &settings, &Palette::grayscale(), |m| messages.push(m.to_owned()));
+
+        assert_eq!(im, render(Point::new(8, 6), &Viewport::default(), &settings, &Palette::grayscale()));
+        assert_eq!(messages.len(), 4, "{messages:?}");
+        assert_eq!(messages[0], "computing counts");
+        assert!(messages[1].starts_with("computing counts took "));
+        assert_eq!(messages[2], "colorizing");
+        assert!(messages[3].starts_with("colorizing took "));
+
+        let mut messages = Vec::new();
+        let path = std::env::temp_dir().join("mandelbrot_test_tiled_log.png");
+        render_tiled_to_file_with_log(Point::new(8, 6), 4, &Viewport::default(), &settings, &Palette::grayscale(), path.to_str().unwrap(), |m| {
+            messages.push(m.to_owned())
+        })
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(messages.iter().any(|m| m == "rendering tiles"), "{messages:?}");
+        assert!(messages.iter().any(|m| m == "finishing file"), "{messages:?}");
+    }
+
+    #[test]
+    fn iteration_parity_alternates_between_two_colors() {
+        let exterior = ExteriorColoring::IterationParity { even: Color::WHITE, odd: Color::RED };
+        let settings = RenderSettings { exterior, ..RenderSettings::default() };
+        let palette = Palette::grayscale();
+        let z = Complex::new(0.0, 0.0);
+
+        for count in 0..20 {
+            let expected = if count % 2 == 0 { Color::WHITE } else { Color::RED };
+            assert!(color_count(z, Some(count as f32), &settings, &palette).approx_eq(expected, 0.0), "count {count}");
+            assert!(color_count(z, Some(count as f32 + 0.7), &settings, &palette).approx_eq(expected, 0.0), "count {count}.7");
+        }
+
+        assert!(ExteriorColoring::Palette.color(3.0).is_none());
+    }
+
+    #[test]
+    fn palette_cycle_apng_has_every_frame() {
+        let size = Point::new(16, 12);
+        let viewport = Viewport::default();
+        let settings = RenderSettings::default();
+        let counts = compute_counts(size, &viewport, &settings.compute);
+        let palette = Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE, Color::RED]);
+        let path = std::env::temp_dir().join("mandelbrot_test_cycle.png");
+        save_palette_cycle_apng(&counts, &viewport, &settings, &palette, 5, 40, path.to_str().unwrap()).unwrap();
+
+        let mut reader = png::Decoder::new(io::BufReader::new(File::open(&path).unwrap())).read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (5, 0));
+
+        let mut frames = Vec::new();
+        for _ in 0..5 {
+            let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+            reader.next_frame(&mut buf).unwrap();
+            frames.push(buf);
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(frames[0].len(), 16 * 12 * 3);
+        assert_ne!(frames[0], frames[1]);
+    }
+
+    #[test]
+    fn adaptive_de_samples_only_the_boundary() {
+        let size = Point::new(48, 36);
+        let viewport = Viewport::default();
+        let settings = RenderSettings::default();
+        let palette = Palette::new(vec![Color::BLACK, Color::WHITE]);
+        let pixel_size = viewport.visible_height() / size.y as f32;
+        let mut at_max = 0;
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let p = Point::new(x, y);
+                let (_, samples) = adaptive_de_pixel(p, size, &viewport, &settings, &palette, 4.0, 16);
+                assert!(samples <= 16);
+
+                let point = |dx: f32, dy: f32| viewport.subpixel_to_complex(Point::new(x as f32 + dx, y as f32 + dy), size);
+                let far = distance_estimate(point(0.5, 0.5), settings.compute.max_iter).is_some_and(|de| de / pixel_size >= 4.0);
+                let interior = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].iter().all(|&(dx, dy)| in_main_cardioid(point(dx, dy)));
+                if far || interior {
+                    assert_eq!(samples, 1, "at {p:?}");
+                } else if samples == 16 {
+                    at_max += 1;
+                }
+            }
+        }
+
+        assert!(at_max > 0);
+    }
+
+    #[test]
+    fn opposite_phases_get_complementary_hues() {
+        for angle in [0.0f32, 0.4, 1.3, 2.0, 3.0, 4.5, 6.0] {
+            let z = Complex::new(3.0 * angle.cos(), 3.0 * angle.sin());
+            let (a, b) = (domain_color(z), domain_color(Complex::new(0.0, 0.0) - z));
+
+            let apart = (a.to_hsv()[0] - b.to_hsv()[0]).rem_euclid(1.0);
+            assert!((apart - 0.5).abs() < 1e-4, "hues {apart} apart at {angle}");
+
+            // Complementary colors of the same brightness add up to a gray.
+            let sum = a + b;
+            assert!(sum.approx_eq(Color::grayscale(sum.r), 1e-4), "{a:?} and {b:?} at {angle}");
+        }
+    }
+
+    #[test]
+    fn blocked_render_matches_render() {
+        let viewport = Viewport::new(Complex::new(-0.75, 0.1), 0.3);
+        let palette = Palette::new(vec![Color::BLACK, Color::RED, Color::WHITE]);
+        let settings = RenderSettings::default();
+
+        for size in [Point::new(24, 16), Point::new(23, 17), Point::new(1, 1), Point::new(5, 1)] {
+            assert_eq!(render_blocked(size, &viewport, &settings, &palette), render(size, &viewport, &settings, &palette), "{size:?}");
+        }
+    }
+
+    #[test]
+    fn blocked_render_matches_render_with_smooth_counts() {
+        let palette = Palette::new(vec![Color::BLACK, Color::RED, Color::WHITE]);
+
+        for smooth_lut in [false, true] {
+            let mut settings = RenderSettings::default();
+            settings.compute.smooth = true;
+            settings.compute.smooth_lut = smooth_lut;
+
+            for viewport in [Viewport::default(), Viewport::new(Complex::new(-0.75, 0.1), 0.3), Viewport::new(Complex::new(-0.745, 0.113), 0.002)] {
+                for size in [Point::new(24, 16), Point::new(23, 17)] {
+                    let expected = render(size, &viewport, &settings, &palette);
+                    assert_eq!(render_blocked(size, &viewport, &settings, &palette), expected, "{viewport:?} at {size:?} with table {smooth_lut}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dual_palettes_color_their_own_pixels() {
+        let size = Point::new(32, 24);
+        let viewport = Viewport::default();
+        let settings = RenderSettings::default();
+        let counts = compute_counts(size, &viewport, &settings.compute);
+        // Every color of the exterior palette is fully red without any blue, and the other way around for the interior.
+        let palettes = DualPalette { exterior: Palette::new(vec![Color::RED, Color::new(1.0, 1.0, 0.0)]), interior: Palette::new(vec![Color::BLUE, Color::new(0.0, 1.0, 1.0)]) };
+
+        for metric in [InteriorMetric::Period, InteriorMetric::FinalAngle] {
+            let colors = colorize_dual(&counts, &viewport, &settings, &palettes, metric);
+            let exterior = colorize(&counts, &viewport, &settings, &palettes.exterior);
+            let mut interior_pixels = 0;
+
+            for (i, count) in counts.data.iter().enumerate() {
+                let color = colors.data[i];
+                if count.is_some() {
+                    assert!(color.approx_eq(exterior.data[i], 0.0), "{color:?} at {i}");
+                    assert!(color.r == 1.0 && color.b == 0.0, "{color:?} at {i}");
+                } else {
+                    assert!(color.r.abs() < 1e-6 && (color.b - 1.0).abs() < 1e-6, "{color:?} at {i}");
+                    interior_pixels += 1;
+                }
+            }
+
+            assert!(interior_pixels > 0);
+        }
+    }
+
+    #[test]
+    fn sixteen_bit_output_keeps_more_of_a_gradient() {
+        let size = Point::new(512, 1);
+        let gradient = Field::new((0..512).map(|x| Color::grayscale(0.2 + 0.02 * x as f32 / 511.0)).collect(), size);
+        let distinct = |depth: BitDepth| {
+            let path = std::env::temp_dir().join(format!("mandelbrot_test_depth_{depth:?}.png"));
+            save_colors(&gradient, depth, path.to_str().unwrap()).unwrap();
+            let image = image::open(&path).unwrap().into_rgb16();
+            std::fs::remove_file(&path).unwrap();
+
+            let mut values: Vec<u16> = image.pixels().map(|p| p.0[0]).collect();
+            values.dedup();
+            values.len()
+        };
+
+        let (eight, sixteen) = (distinct(BitDepth::Eight), distinct(BitDepth::Sixteen));
+        // The gradient spans about five steps of an 8-bit channel, but every pixel gets its own 16-bit value.
+        assert!(eight <= 7, "{eight}");
+        assert_eq!(sixteen, 512);
+    }
+
+    #[test]
+    fn colorized_counts_match_render() {
+        let size = Point::new(24, 18);
+        let palette = Palette::new(vec![Color::BLACK, Color::RED, Color::WHITE]);
+        let viewports = [
+            Viewport::default(),
+            Viewport::new(Complex::new(-0.75, 0.1), 0.05),
+            Viewport::new(Complex::new(-1.25, 0.0), 0.2),
+            Viewport::with_scales(Complex::new(0.3, 0.5), 0.3, 0.1),
+        ];
+
+        for smooth in [false, true] {
+            let mut settings = RenderSettings::default();
+            settings.compute.smooth = smooth;
+
+            for viewport in &viewports {
+                let colors = colorize(&compute_counts(size, viewport, &settings.compute), viewport, &settings, &palette);
+                let im = MandelbrotImage::from_fn(size.x, size.y, |x, y| colors.data[colors.index(Point::new(x, y))].into());
+                assert_eq!(im, render(size, viewport, &settings, &palette), "{viewport:?} with smooth {smooth}");
+            }
+        }
+    }
+
+    #[test]
+    fn max_iter_normalization_at_or_below_min_iter_gives_the_last_color() {
+        for (min_iter, max_iter) in [(100, 100), (200, 100), (5, 0)] {
+            let t = NormalizeMode::MaxIter.normalize(50.0, min_iter, max_iter);
+            assert_eq!(t, 1.0, "min_iter {min_iter} and max_iter {max_iter}");
+        }
+
+        assert_eq!(NormalizeMode::MaxIter.normalize(60.0, 10, 110), 0.5);
+    }
+}
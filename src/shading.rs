@@ -0,0 +1,134 @@
+#![allow(unused)]
+
+use image::{ImageResult, Rgb};
+
+use crate::compute::{compute_counts, Viewport};
+use crate::field::Field;
+use crate::palette::Palette;
+use crate::render::{colorize, RenderSettings};
+use crate::util::{ComplexF32, MandelbrotImage, Point};
+
+/// Computes the surface normal of the escape count field at pixel `p`, treating counts as heights.
+///
+/// The normal is returned as `(x, y, z)` in tangent space: `x` points right, `y` points up
+/// and `z` points out of the image. `strength` scales the slopes before the normal is computed,
+/// so larger values exaggerate the relief.
+///
+/// Points inside the set have no count, so they are treated as being level with the center pixel.
+pub fn field_normal(counts: &Field<Option<f32>>, p: Point<u32>, strength: f32) -> (f32, f32, f32) {
+    let Some(&Some(center)) = counts.get(p) else {
+        return (0.0, 0.0, 1.0);
+    };
+
+    let [left, right, up, down] = counts.neighbors(p).map(|h| h.unwrap_or(center));
+
+    let nx = -(right - left) * 0.5 * strength;
+    let ny = -(up - down) * 0.5 * strength;
+    let len = (nx * nx + ny * ny + 1.0).sqrt();
+
+    (nx / len, ny / len, 1.0 / len)
+}
+
+/// Saves the slopes of an escape count field as a tangent-space normal map.
+///
+/// Each normal is mapped from `[-1, 1]` to `[0, 1]` and stored in the red, green and blue channels,
+/// so flat regions come out as the familiar `(0.5, 0.5, 1.0)` blue.
+pub fn save_normal_map(counts: &Field<Option<f32>>, path: &str, strength: f32) -> ImageResult<()> {
+    let size = counts.size;
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let (nx, ny, nz) = field_normal(counts, Point::new(x, y), strength);
+            let encode = |v: f32| ((v * 0.5 + 0.5) * 255.0).round() as u8;
+
+            im.put_pixel(x, y, Rgb([encode(nx), encode(ny), encode(nz)]));
+        }
+    }
+
+    im.save(path)
+}
+
+/// Renders the Mandelbrot set colored by `palette`, with its brightness modulated by the slopes of the escape counts
+/// to give it an embossed, three-dimensional look.
+///
+/// `light` is the direction in the image plane that the light comes from, with the imaginary part pointing up.
+/// It is raised by one unit out of the image, so longer vectors make for a lower light and stronger shading.
+/// `height_scale` is passed to [`field_normal`] as the strength of the relief.
+///
+/// The shading is scaled so that flat regions, including the inside of the set, keep the palette color unchanged,
+/// while slopes facing the light are brightened and slopes facing away from it are darkened.
+pub fn render_slope_shaded(
+    size: Point<u32>,
+    viewport: &Viewport,
+    settings: &RenderSettings,
+    palette: &Palette,
+    light: ComplexF32,
+    height_scale: f32,
+) -> MandelbrotImage {
+    let counts = compute_counts(size, viewport, &settings.compute);
+    let colors = colorize(&counts, viewport, settings, palette);
+    let mut im = MandelbrotImage::new(size.x, size.y);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let p = Point::new(x, y);
+            let (nx, ny, nz) = field_normal(&counts, p, height_scale);
+            let shade = (nx * light.re + ny * light.im + nz).max(0.0);
+
+            im.put_pixel(x, y, (colors.data[colors.index(p)] * shade).into());
+        }
+    }
+
+    im
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::render::{render, InteriorColoring};
+    use crate::util::{Color, Complex};
+
+    #[test]
+    fn normal_map_is_flat_blue_except_on_slopes() {
+        // Level on the left, rising steeply to the right.
+        let size = Point::new(8, 3);
+        let data = (0..size.y).flat_map(|_| (0..size.x).map(|x| Some(if x < 3 { 5.0 } else { 5.0 + 10.0 * (x - 2) as f32 }))).collect();
+        let counts = Field::new(data, size);
+
+        let path = std::env::temp_dir().join("mandelbrot_test_normal_map.png");
+        save_normal_map(&counts, path.to_str().unwrap(), 1.0).unwrap();
+        let im = image::open(&path).unwrap().into_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*im.get_pixel(0, 1), Rgb([128, 128, 255]));
+        assert_eq!(*im.get_pixel(1, 0), Rgb([128, 128, 255]));
+
+        let Rgb([r, g, b]) = *im.get_pixel(5, 1);
+        assert!(r < 64, "a slope rising to the right should tilt the normal left, got {r}");
+        assert_eq!(g, 128);
+        assert!(b < 200);
+    }
+
+    #[test]
+    fn slope_shading_leaves_flat_regions_and_shades_slopes() {
+        let size = Point::new(16, 12);
+        let palette = Palette::new(vec![Color::grayscale(0.5)]);
+        let mut settings = RenderSettings { interior: InteriorColoring::Solid(Color::grayscale(0.5)), ..RenderSettings::default() };
+        settings.compute.smooth = true;
+
+        // Entirely inside the main cardioid, so there are no slopes at all.
+        let inside = Viewport::new(Complex::new(-0.1, 0.0), 0.05);
+        let shaded = render_slope_shaded(size, &inside, &settings, &palette, Complex::new(1.0, 0.5), 2.0);
+        assert_eq!(shaded, render(size, &inside, &settings, &palette));
+
+        // Just right of the cusp, where the counts climb towards the set to the left.
+        let outside = Viewport::new(Complex::new(0.45, 0.0), 0.1);
+        let lit = |light| render_slope_shaded(size, &outside, &settings, &palette, light, 2.0).get_pixel(8, 6).0[0];
+        let flat = render(size, &outside, &settings, &palette).get_pixel(8, 6).0[0];
+
+        assert!(lit(Complex::new(1.0, 0.0)) > flat, "a slope facing the light should be brightened");
+        assert!(lit(Complex::new(-1.0, 0.0)) < flat, "a slope facing away from the light should be darkened");
+    }
+}
@@ -1,12 +1,31 @@
 #![allow(unused)]
 
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Mul};
+use std::time::Instant;
 
-use image::{ImageBuffer, Pixel, PixelWithColorType, Rgb};
+use image::{ImageBuffer, Pixel, PixelWithColorType, Rgb, Rgba};
+
+// The math types live in their own module, so that computing escape counts does not depend on `image`.
+pub use crate::math::{real_pow, Complex, ComplexF32, ComplexF64, Point};
+pub(crate) use crate::math::{Abs, Hypot};
 
 pub type MandelbrotImage = ImageBuffer<Rgb<u8>, Vec<u8>>;
 
+/// Like [`MandelbrotImage`], but with 16 bits per channel.
+pub type MandelbrotImage16 = ImageBuffer<Rgb<u16>, Vec<u16>>;
+
+/// Runs `f` as the phase `phase` of a longer job, reporting to `log` when it starts and how long it took.
+pub fn timed<T>(log: &mut impl FnMut(&str), phase: &str, f: impl FnOnce() -> T) -> T {
+    log(phase);
+    let start = Instant::now();
+    let result = f();
+    log(&format!("{phase} took {:.2?}", start.elapsed()));
+
+    result
+}
+
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -16,6 +35,9 @@ pub struct Color {
 impl Color {
     pub const BLACK: Color = Color::new(0.0, 0.0, 0.0);
     pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    pub const RED: Color = Color::new(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::new(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::new(0.0, 0.0, 1.0);
 
     #[inline]
     pub const fn new(r: f32, g: f32, b: f32) -> Color {
@@ -27,6 +49,36 @@ impl Color {
         Color { r: l, g: l, b: l }
     }
 
+    /// Approximates the color of a blackbody radiating at `temp` kelvin.
+    ///
+    /// Uses Tanner Helland's curve fit of the blackbody spectrum, which is accurate enough for
+    /// palettes over the range it was fit to. Temperatures are clamped to `1000K..=40000K`.
+    pub fn from_kelvin(temp: f32) -> Color {
+        let t = temp.clamp(1000.0, 40000.0) / 100.0;
+
+        let r = if t <= 66.0 {
+            255.0
+        } else {
+            329.698_73 * (t - 60.0).powf(-0.133_204_76)
+        };
+
+        let g = if t <= 66.0 {
+            99.470_8 * t.ln() - 161.119_57
+        } else {
+            288.122_17 * (t - 60.0).powf(-0.075_514_85)
+        };
+
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_73 * (t - 10.0).ln() - 305.044_8
+        };
+
+        Color::new(r, g, b).clamp(0.0, 255.0) * (1.0 / 255.0)
+    }
+
     #[inline]
     pub fn cos(self) -> Color {
         Color {
@@ -36,6 +88,230 @@ impl Color {
         }
     }
 
+    /// Whether every channel of this color is within `eps` of the same channel of `other`.
+    #[inline]
+    pub fn approx_eq(self, other: Color, eps: f32) -> bool {
+        (self.r - other.r).abs() <= eps && (self.g - other.g).abs() <= eps && (self.b - other.b).abs() <= eps
+    }
+
+    /// Whether this color is black, allowing each channel to be off by up to `eps`.
+    #[inline]
+    pub fn is_black(self, eps: f32) -> bool {
+        self.approx_eq(Color::BLACK, eps)
+    }
+
+    /// The relative luminance of this color, using the Rec. 709 channel weights.
+    #[inline]
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Converts this color to a shade of gray using the given method.
+    #[inline]
+    pub fn to_grayscale(self, method: GrayMethod) -> Color {
+        let l = match method {
+            GrayMethod::Luminance => self.luminance(),
+            GrayMethod::Average => (self.r + self.g + self.b) / 3.0,
+            GrayMethod::Lightness => (self.r.max(self.g).max(self.b) + self.r.min(self.g).min(self.b)) / 2.0,
+        };
+
+        Color::grayscale(l)
+    }
+
+    /// Moves this color towards the gray of the same luminance by `amount`,
+    /// where `0` leaves it unchanged and `1` turns it fully gray.
+    #[inline]
+    pub fn desaturate(self, amount: f32) -> Color {
+        self.lerp(Color::grayscale(self.luminance()), amount)
+    }
+
+    /// Moves this color away from the gray of the same luminance by `amount`, making it more vivid.
+    ///
+    /// The result is clamped to `[0, 1]`, so very large amounts push the channels to their limits.
+    #[inline]
+    pub fn saturate(self, amount: f32) -> Color {
+        self.lerp(Color::grayscale(self.luminance()), -amount).clamp(0.0, 1.0)
+    }
+
+    /// Brings this color into `[0, 1]` by desaturating it just enough, keeping its luminance and hue.
+    ///
+    /// Clamping each channel on its own, as the conversion to `Rgb<u8>` does, shifts the hue of colors that are out
+    /// of gamut, so `Rgb::from(color.clip_to_gamut())` can be used instead where that matters. Colors with a luminance
+    /// outside of `[0, 1]` have no such gray to move towards and become black or white.
+    pub fn clip_to_gamut(self) -> Color {
+        let luma = self.luminance();
+
+        if luma <= 0.0 {
+            return Color::BLACK;
+        } else if luma >= 1.0 {
+            return Color::WHITE;
+        }
+
+        // The most saturation each channel allows, going from the gray of the same luminance towards the color.
+        let limit = |v: f32| match v {
+            v if v > 1.0 => (1.0 - luma) / (v - luma),
+            v if v < 0.0 => luma / (luma - v),
+            _ => 1.0,
+        };
+        let saturation = limit(self.r).min(limit(self.g)).min(limit(self.b));
+
+        self.desaturate(1.0 - saturation).clamp(0.0, 1.0)
+    }
+
+    /// Linearly interpolates between this color and `other` by `t`.
+    #[inline]
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        self * (1.0 - t) + other * t
+    }
+
+    /// Interpolates between `a` and `b` by `t`, then rescales the result so that its luminance is `target_luma`.
+    ///
+    /// This keeps the hue and saturation of the blend while stopping the middle of a gradient from getting darker
+    /// or lighter than its ends. If scaling up would push a channel past `1`, the color is instead moved towards white
+    /// until it reaches `target_luma`, which should lie in `[0, 1]`. Blends that are pure black come out gray.
+    pub fn mix_preserve_luminance(a: Color, b: Color, t: f32, target_luma: f32) -> Color {
+        let mixed = a.lerp(b, t);
+        let luma = mixed.luminance();
+
+        if luma <= 0.0 {
+            return Color::grayscale(target_luma);
+        }
+
+        let scaled = mixed * (target_luma / luma);
+        let max = scaled.r.max(scaled.g).max(scaled.b);
+
+        if max <= 1.0 {
+            return scaled;
+        }
+
+        let brightest = scaled * (1.0 / max);
+        let luma = brightest.luminance();
+        brightest.lerp(Color::WHITE, (target_luma - luma) / (1.0 - luma))
+    }
+
+    /// Interpolates between this color and `other` by `t`, with the interpolation done in `space`.
+    ///
+    /// Colors are stored as sRGB, so [`BlendSpace::Srgb`] is the same as [`Color::lerp`].
+    pub fn lerp_in(self, other: Color, t: f32, space: BlendSpace) -> Color {
+        match space {
+            BlendSpace::Srgb => self.lerp(other, t),
+            BlendSpace::LinearRgb => self.to_linear().lerp(other.to_linear(), t).to_srgb(),
+            BlendSpace::Oklab => Color::from_oklab(lerp3(self.to_oklab(), other.to_oklab(), t)),
+            BlendSpace::Hsv => Color::from_hsv(lerp_hsv(self.to_hsv(), other.to_hsv(), t)),
+        }
+    }
+
+    /// Converts this color from sRGB to linear RGB, undoing the sRGB transfer curve.
+    #[inline]
+    pub fn to_linear(self) -> Color {
+        self.map(|c| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        })
+    }
+
+    /// Converts this color from linear RGB back to sRGB, the inverse of [`Color::to_linear`].
+    #[inline]
+    pub fn to_srgb(self) -> Color {
+        self.map(|c| {
+            if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        })
+    }
+
+    /// Converts this color to Björn Ottosson's Oklab space, as `[L, a, b]`.
+    pub fn to_oklab(self) -> [f32; 3] {
+        let c = self.to_linear();
+
+        let l = (0.412_221_46 * c.r + 0.536_332_55 * c.g + 0.051_445_995 * c.b).cbrt();
+        let m = (0.211_903_5 * c.r + 0.680_699_5 * c.g + 0.107_396_96 * c.b).cbrt();
+        let s = (0.088_302_46 * c.r + 0.281_718_85 * c.g + 0.629_978_7 * c.b).cbrt();
+
+        [
+            0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        ]
+    }
+
+    /// Converts a color in Oklab space, given as `[L, a, b]`, back to sRGB.
+    pub fn from_oklab(lab: [f32; 3]) -> Color {
+        let [l, a, b] = lab;
+
+        let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+        let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+        let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+        let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+
+        Color::new(
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        )
+        .to_srgb()
+    }
+
+    /// Converts this color to hue, saturation and value, as `[h, s, v]`.
+    ///
+    /// All three are in `[0, 1]`, with the hue measured in turns starting from red.
+    pub fn to_hsv(self) -> [f32; 3] {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        [h / 6.0, s, max]
+    }
+
+    /// Converts a color given as `[h, s, v]`, in the same form as [`Color::to_hsv`], back to RGB.
+    pub fn from_hsv(hsv: [f32; 3]) -> Color {
+        let [h, s, v] = hsv;
+        let h = h.rem_euclid(1.0) * 6.0;
+        let channel = |n: f32| {
+            let k = (n + h) % 6.0;
+            v - v * s * k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+
+        Color::new(channel(5.0), channel(3.0), channel(1.0))
+    }
+
+    /// The 24-bit ANSI escape sequence that sets the terminal's background to this color.
+    pub fn to_ansi_bg(self) -> String {
+        let Rgb([r, g, b]) = Rgb::<u8>::from(self);
+        format!("\x1b[48;2;{r};{g};{b}m")
+    }
+
+    /// The 24-bit ANSI escape sequence that sets the terminal's foreground (text) color to this color.
+    pub fn to_ansi_fg(self) -> String {
+        let Rgb([r, g, b]) = Rgb::<u8>::from(self);
+        format!("\x1b[38;2;{r};{g};{b}m")
+    }
+
+    #[inline]
+    fn map(self, f: impl Fn(f32) -> f32) -> Color {
+        Color {
+            r: f(self.r),
+            g: f(self.g),
+            b: f(self.b),
+        }
+    }
+
     #[inline]
     fn clamp(self, low: f32, high: f32) -> Color {
         Color {
@@ -46,6 +322,80 @@ impl Color {
     }
 }
 
+/// A way of converting a color to a shade of gray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrayMethod {
+    /// Weights each channel by how bright it appears to the eye (Rec. 709), so `RED` becomes `0.2126`.
+    Luminance,
+    /// Takes the plain average of the channels, so `RED` becomes `1/3`.
+    Average,
+    /// Takes the average of the largest and smallest channel, as in HSL, so `RED` becomes `0.5`.
+    Lightness,
+}
+
+/// The color space that colors are interpolated in when blending them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendSpace {
+    /// Interpolates the physical light intensities, which keeps blends of bright colors from turning muddy.
+    LinearRgb,
+    /// Interpolates the stored sRGB values directly.
+    #[default]
+    Srgb,
+    /// Interpolates in Oklab, which changes perceived lightness and hue evenly along the way.
+    Oklab,
+    /// Interpolates hue, saturation and value, sweeping through the hues that lie between the two colors.
+    ///
+    /// The hue takes the shorter way around the color wheel, so blending `350°` with `10°` passes through red
+    /// rather than cyan. Grays have no hue of their own, so blending with one keeps the hue of the other color.
+    Hsv,
+}
+
+/// Linearly interpolates between two triples of components by `t`.
+#[inline]
+fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Interpolates between two colors given as `[h, s, v]` by `t`, taking the shorter way around the hue circle.
+#[inline]
+fn lerp_hsv(mut a: [f32; 3], mut b: [f32; 3], t: f32) -> [f32; 3] {
+    if a[1] == 0.0 {
+        a[0] = b[0];
+    } else if b[1] == 0.0 {
+        b[0] = a[0];
+    }
+
+    let dh = (b[0] - a[0] + 0.5).rem_euclid(1.0) - 0.5;
+    let [_, s, v] = lerp3(a, b, t);
+
+    [(a[0] + dh * t).rem_euclid(1.0), s, v]
+}
+
+macro_rules! impl_swizzle {
+    ($($name:ident: $r:ident $g:ident $b:ident);*) => {
+        impl Color {
+            $(
+                #[inline]
+                #[doc = concat!("Reorders the channels of this color so that its red, green and blue come from `", stringify!($r), "`, `", stringify!($g), "` and `", stringify!($b), "`.")]
+                pub const fn $name(self) -> Color {
+                    Color::new(self.$r, self.$g, self.$b)
+                }
+            )*
+        }
+    }
+}
+
+impl_swizzle!(
+    rgb: r g b;
+    rbg: r b g;
+    grb: g r b;
+    gbr: g b r;
+    brg: b r g;
+    bgr: b g r
+);
+
 impl Add<Color> for Color {
     type Output = Color;
 
@@ -99,6 +449,9 @@ impl Mul<Color> for f32 {
 }
 
 impl From<Color> for Rgb<u8> {
+    /// Converts a color to 8 bits per channel, clamping each channel to `[0, 1]`.
+    ///
+    /// See [`Color::clip_to_gamut`] for a way to bring colors into range that keeps their hue.
     fn from(v: Color) -> Self {
         let c = v.clamp(0.0, 1.0);
         Rgb([
@@ -109,220 +462,320 @@ impl From<Color> for Rgb<u8> {
     }
 }
 
-/// A point in 2D space.
-#[derive(Clone, Copy, Debug)]
-pub struct Point<T> {
-    pub x: T,
-    pub y: T,
+impl From<Color> for Rgb<u16> {
+    /// Converts a color to 16 bits per channel, clamping each channel to `[0, 1]` like the 8-bit conversion.
+    fn from(v: Color) -> Self {
+        let c = v.clamp(0.0, 1.0);
+        Rgb([
+            (c.r * 65535.0) as u16,
+            (c.g * 65535.0) as u16,
+            (c.b * 65535.0) as u16,
+        ])
+    }
+}
+
+impl From<Rgb<u8>> for Color {
+    fn from(v: Rgb<u8>) -> Self {
+        Color::new(
+            v.0[0] as f32 / 255.0,
+            v.0[1] as f32 / 255.0,
+            v.0[2] as f32 / 255.0,
+        )
+    }
+}
+
+/// A running weighted sum of colors, for averaging the samples of a pixel under a reconstruction filter.
+///
+/// Each sample is added with the filter's weight at its position, and [`ColorAccumulator::mean`] divides
+/// by the total weight, so the weights do not need to be normalized up front.
+#[derive(Debug, Clone, Copy)]
+pub struct ColorAccumulator {
+    sum: Color,
+    weight: f32,
 }
 
-impl<T> Point<T> {
-    /// Creates a new instance of `Point` given an x and y position.
+impl ColorAccumulator {
+    /// Creates an empty `ColorAccumulator`.
     #[inline]
-    pub const fn new(x: T, y: T) -> Point<T> {
-        Self { x, y }
+    pub const fn new() -> ColorAccumulator {
+        ColorAccumulator { sum: Color::BLACK, weight: 0.0 }
     }
 
-    /// Maps a function over the x and y parts of this point.
+    /// Adds the color `c` with weight `w`.
     #[inline]
-    pub fn map<F: Fn(T) -> U, U>(self, f: F) -> Point<U> {
-        Point::<U> {
-            x: f(self.x),
-            y: f(self.y),
-        }
+    pub fn add_weighted(&mut self, c: Color, w: f32) {
+        self.sum = self.sum + c * w;
+        self.weight += w;
     }
-}
 
-impl Point<u32> {
-    /// Converts a point from pixel coordinates to uv coordinates given the size of the image.
+    /// The total weight of every color added so far.
     #[inline]
-    pub fn to_uv(self, n: u32) -> Point<f32> {
-        self.map(|v| v as f32 / n as f32)
+    pub fn weight(&self) -> f32 {
+        self.weight
     }
-}
 
-impl<T: Default> Default for Point<T> {
-    fn default() -> Self {
-        Self {
-            x: T::default(),
-            y: T::default(),
+    /// The weighted average of every color added so far, or black if the total weight is not positive.
+    #[inline]
+    pub fn mean(&self) -> Color {
+        if self.weight <= 0.0 {
+            Color::BLACK
+        } else {
+            self.sum * (1.0 / self.weight)
         }
     }
 }
 
-/// A complex number of type `f32`.
-pub type ComplexF32 = Complex<f32>;
+impl Default for ColorAccumulator {
+    fn default() -> Self {
+        ColorAccumulator::new()
+    }
+}
 
-/// A complex number.
-#[derive(Clone, Copy, Debug)]
-pub struct Complex<T> {
-    pub re: T,
-    pub im: T,
+/// A color with an alpha (opacity) channel.
+///
+/// The color channels are not premultiplied by alpha.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorA {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
 }
 
-impl<T> Complex<T> {
-    /// Creates a new instance of `Complex` with the given real and imaginary parts.
+impl ColorA {
+    pub const TRANSPARENT: ColorA = ColorA::new(0.0, 0.0, 0.0, 0.0);
+
     #[inline]
-    pub const fn new(re: T, im: T) -> Complex<T> {
-        Self { re, im }
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> ColorA {
+        ColorA { r, g, b, a }
     }
-    
-    /// Maps a function over the real and imaginary parts of a complex number.
+
+    /// Creates a `ColorA` from a `Color` and an opacity.
     #[inline]
-    pub fn map<F: Fn(T) -> U, U>(self, f: F) -> Complex<U> {
-        Complex::<U> {
-            re: f(self.re),
-            im: f(self.im),
-        }
+    pub const fn from_color(c: Color, a: f32) -> ColorA {
+        ColorA { r: c.r, g: c.g, b: c.b, a }
     }
 
-    /// Zips two complex numbers together.
+    /// The color channels of this color, without the alpha channel.
     #[inline]
-    pub fn zip<U>(self, rhs: Complex<U>) -> Complex<(T, U)> {
-        Complex::<(T, U)> {
-            re: (self.re, rhs.re),
-            im: (self.im, rhs.im),
-        }
+    pub const fn color(self) -> Color {
+        Color::new(self.r, self.g, self.b)
     }
-}
 
-impl<T> From<Point<T>> for Complex<T> {
-    /// Converts a point to a complex number by directly
-    /// mapping the point's x-value to the real component
-    /// and the point's y-value to the imaginary component.
+    /// Linearly interpolates every channel, including alpha, between this color and `other` by `t`.
     #[inline]
-    fn from(value: Point<T>) -> Self {
-        Self {
-            re: value.x,
-            im: value.y,
-        }
+    pub fn lerp(self, other: ColorA, t: f32) -> ColorA {
+        ColorA::from_color(self.color().lerp(other.color(), t), self.a + (other.a - self.a) * t)
     }
-}
 
-impl<T: Default> Default for Complex<T> {
-    fn default() -> Self {
-        Self {
-            re: T::default(),
-            im: T::default(),
-        }
+    /// Multiplies the color channels by the opacity.
+    #[inline]
+    pub fn premultiply(self) -> ColorA {
+        ColorA::from_color(self.color() * self.a, self.a)
     }
-}
 
-impl<T: Add<T, Output = T>> Add for Complex<T> {
-    type Output = Self;
-
-    /// Adds two complex numbers together.
+    /// Divides the color channels of a premultiplied color by its opacity, undoing [`ColorA::premultiply`].
+    ///
+    /// A fully transparent color has lost its color channels, so it comes out as [`ColorA::TRANSPARENT`].
     #[inline]
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            re: self.re + rhs.re,
-            im: self.im + rhs.im,
+    pub fn unpremultiply(self) -> ColorA {
+        if self.a <= 0.0 {
+            ColorA::TRANSPARENT
+        } else {
+            ColorA::from_color(self.color() * (1.0 / self.a), self.a)
         }
     }
+
+    /// The average of a list of colors, which must not be empty.
+    ///
+    /// The colors are averaged with their channels premultiplied by their opacity. Averaging them directly would
+    /// mix in the meaningless color of transparent samples, darkening the edges of a shape against transparency.
+    pub fn average(colors: &[ColorA]) -> ColorA {
+        let sum = colors.iter().fold(ColorA::TRANSPARENT, |acc, &c| {
+            let c = c.premultiply();
+            ColorA::new(acc.r + c.r, acc.g + c.g, acc.b + c.b, acc.a + c.a)
+        });
+        let n = colors.len() as f32;
+
+        ColorA::new(sum.r / n, sum.g / n, sum.b / n, sum.a / n).unpremultiply()
+    }
 }
 
-impl<T: Sub<T, Output = T>> Sub for Complex<T> {
-    type Output = Self;
+impl From<Color> for ColorA {
+    /// Converts a color to a fully opaque `ColorA`.
+    fn from(v: Color) -> Self {
+        ColorA::from_color(v, 1.0)
+    }
+}
 
-    /// Subtracts one complex number from another.
-    #[inline]
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self {
-            re: self.re - rhs.re,
-            im: self.im - rhs.im,
-        }
+impl From<ColorA> for Rgba<u8> {
+    fn from(v: ColorA) -> Self {
+        let Rgb([r, g, b]) = v.color().into();
+        Rgba([r, g, b, (v.a.clamp(0.0, 1.0) * 255.0) as u8])
     }
 }
 
-impl<T: Clone + Copy + Mul<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T>> Mul for Complex<T> {
-    type Output = Self;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Computes the product of two complex numbers.
-    #[inline]
-    fn mul(self, rhs: Self) -> Self::Output {
-        Self {
-            re: self.re * rhs.re - self.im * rhs.im,
-            im: self.re * rhs.im + self.im * rhs.re,
+    #[test]
+    fn kelvin_colors_match_their_temperatures() {
+        let daylight = Color::from_kelvin(6500.0);
+        assert!(daylight.r > 0.95 && daylight.g > 0.95 && daylight.b > 0.95, "6500K should be near white, got {daylight:?}");
+
+        let candle = Color::from_kelvin(2000.0);
+        assert!(candle.r > 0.95 && (0.3..0.7).contains(&candle.g) && candle.b < 0.2, "2000K should be orange, got {candle:?}");
+    }
+
+    #[test]
+    fn swizzles_reorder_channels() {
+        assert!(Color::RED.bgr().approx_eq(Color::BLUE, 0.0));
+        assert!(Color::RED.gbr().approx_eq(Color::BLUE, 0.0));
+        assert!(Color::RED.grb().approx_eq(Color::GREEN, 0.0));
+
+        let c = Color::new(0.1, 0.2, 0.3);
+        assert!(c.rgb().approx_eq(c, 0.0));
+        assert!(c.brg().approx_eq(Color::new(0.3, 0.1, 0.2), 0.0));
+        assert!(c.bgr().bgr().approx_eq(c, 0.0));
+    }
+
+    #[test]
+    fn gray_methods_differ_on_saturated_colors() {
+        for (method, red, yellow) in [
+            (GrayMethod::Luminance, 0.2126, 0.9278),
+            (GrayMethod::Average, 1.0 / 3.0, 2.0 / 3.0),
+            (GrayMethod::Lightness, 0.5, 0.5),
+        ] {
+            assert!(Color::RED.to_grayscale(method).approx_eq(Color::grayscale(red), 1e-6), "{method:?}");
+            assert!(Color::new(1.0, 1.0, 0.0).to_grayscale(method).approx_eq(Color::grayscale(yellow), 1e-6), "{method:?}");
+            assert!(Color::WHITE.to_grayscale(method).approx_eq(Color::WHITE, 1e-6), "{method:?}");
         }
     }
-}
 
-impl<T: Clone + Copy + Div<T, Output = T> + Add<T, Output = T> + Sub<T, Output = T> + Mul<T, Output = T>> Div for Complex<T> {
-    type Output = Self;
+    #[test]
+    fn desaturate_keeps_luminance() {
+        let c = Color::new(0.9, 0.3, 0.1);
 
-    /// Performs complex division on two complex numbers.
-    #[inline]
-    fn div(self, rhs: Self) -> Self::Output {
-        Self {
-            re: (self.re * rhs.re + self.im * rhs.im) / (rhs.re * rhs.re + rhs.im * rhs.im),
-            im: (self.im * rhs.re - self.re * rhs.im) / (rhs.re * rhs.re + rhs.im * rhs.im),
+        let gray = c.desaturate(1.0);
+        assert!((gray.luminance() - c.luminance()).abs() < 1e-6);
+        assert!((gray.r - gray.g).abs() < 1e-6 && (gray.g - gray.b).abs() < 1e-6, "{gray:?}");
+
+        assert!(c.desaturate(0.0).approx_eq(c, 0.0));
+        assert!((c.desaturate(0.5).luminance() - c.luminance()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn blend_spaces_give_different_midpoints() {
+        let spaces = [BlendSpace::Srgb, BlendSpace::LinearRgb, BlendSpace::Oklab, BlendSpace::Hsv];
+        let midpoints = spaces.map(|space| Color::RED.lerp_in(Color::BLUE, 0.5, space));
+
+        for i in 0..spaces.len() {
+            for j in i + 1..spaces.len() {
+                assert!(!midpoints[i].approx_eq(midpoints[j], 1e-2), "{:?} and {:?} agree", spaces[i], spaces[j]);
+            }
         }
+
+        assert!(midpoints[0].approx_eq(Color::new(0.5, 0.0, 0.5), 1e-6));
+        assert!(midpoints[1].r > 0.7 && midpoints[1].b > 0.7, "{:?}", midpoints[1]);
+        // The shorter way from red to blue around the hue circle passes through magenta.
+        assert!(midpoints[3].approx_eq(Color::new(1.0, 0.0, 1.0), 1e-5), "{:?}", midpoints[3]);
     }
-}
 
-impl<T> From<(T, T)> for Complex<T> {
-    /// Converts a tuple into a complex number.
-    fn from(value: (T, T)) -> Self {
-        Complex::new(value.0, value.1)
+    #[test]
+    fn mix_preserve_luminance_hits_the_target() {
+        for (a, b) in [(Color::RED, Color::BLUE), (Color::new(0.1, 0.6, 0.2), Color::new(0.9, 0.9, 0.1)), (Color::BLACK, Color::BLACK)] {
+            for target in [0.0, 0.2, 0.5, 0.95] {
+                for t in [0.0, 0.3, 0.5, 1.0] {
+                    let mixed = Color::mix_preserve_luminance(a, b, t, target);
+                    assert!((mixed.luminance() - target).abs() < 1e-5, "{a:?} to {b:?} at {t} for {target}: {mixed:?}");
+                    assert!([mixed.r, mixed.g, mixed.b].iter().all(|c| (0.0..=1.0 + 1e-6).contains(c)), "{mixed:?}");
+                }
+            }
+        }
     }
-}
 
-impl<T> From<Complex<T>> for (T, T) {
-    /// Converts a complex number into a tuple.
-    fn from(value: Complex<T>) -> Self {
-        (value.re, value.im)
+    #[test]
+    fn ansi_escapes_encode_the_color() {
+        let orange = Color::new(1.0, 0.2, 0.0);
+        assert_eq!(orange.to_ansi_bg(), "\x1b[48;2;255;51;0m");
+        assert_eq!(orange.to_ansi_fg(), "\x1b[38;2;255;51;0m");
+        assert_eq!(Color::new(2.0, -1.0, 0.0).to_ansi_fg(), "\x1b[38;2;255;0;0m");
     }
-}
 
-#[allow(private_bounds)]
-impl<T: Hypot> Complex<T> {
-    /// Computes the absolute value (magnitude) of a complex number.
-    #[inline]
-    pub fn abs(self) -> T {
-        self.re.hypotenuse(self.im)
+    #[test]
+    fn averaging_with_transparency_keeps_the_opaque_color() {
+        let opaque = ColorA::new(0.8, 0.4, 0.2, 1.0);
+        // The color channels of a transparent sample should not matter at all.
+        for transparent in [ColorA::TRANSPARENT, ColorA::new(1.0, 1.0, 1.0, 0.0)] {
+            let average = ColorA::average(&[opaque, transparent]);
+            assert!(average.color().approx_eq(opaque.color(), 1e-6), "{average:?}");
+            assert!((average.a - 0.5).abs() < 1e-6, "{average:?}");
+        }
     }
-}
 
+    #[test]
+    fn hsv_blends_wrap_around_red() {
+        let a = Color::from_hsv([350.0 / 360.0, 1.0, 1.0]);
+        let b = Color::from_hsv([10.0 / 360.0, 1.0, 1.0]);
 
-trait Hypot {
-    fn hypotenuse(self, rhs: Self) -> Self;
-}
+        let midpoint = a.lerp_in(b, 0.5, BlendSpace::Hsv);
+        assert!(midpoint.approx_eq(Color::RED, 1e-5), "{midpoint:?}");
 
-impl Hypot for f32 {
-    fn hypotenuse(self, rhs: Self) -> Self {
-        f32::hypot(self, rhs)
+        // Every step stays within the 20° arc around red instead of sweeping through cyan.
+        for i in 0..=10 {
+            let [h, ..] = a.lerp_in(b, i as f32 / 10.0, BlendSpace::Hsv).to_hsv();
+            let from_red = h.min(1.0 - h) * 360.0;
+            assert!(from_red <= 10.0 + 1e-3, "{from_red}° from red at step {i}");
+        }
     }
-}
 
-impl Hypot for f64 {
-    fn hypotenuse(self, rhs: Self) -> Self {
-        f64::hypot(self, rhs)
+    #[test]
+    fn gamut_clipping_keeps_the_hue() {
+        let c = Color::new(1.6, 0.8, 0.2);
+        let [hue, ..] = c.to_hsv();
+
+        let clipped = c.clip_to_gamut();
+        assert!([clipped.r, clipped.g, clipped.b].iter().all(|v| (0.0..=1.0).contains(v)), "{clipped:?}");
+        assert!((clipped.to_hsv()[0] - hue).abs() < 1e-5, "{clipped:?}");
+        assert!((clipped.luminance() - c.luminance()).abs() < 1e-5, "{clipped:?}");
+
+        // Clamping each channel on its own turns this orange noticeably yellower.
+        let clamped = Color::new(c.r.min(1.0), c.g.min(1.0), c.b.min(1.0));
+        assert!(clamped.to_hsv()[0] - hue > 0.03, "{clamped:?}");
+
+        let in_gamut = Color::new(0.9, 0.3, 0.1);
+        assert!(in_gamut.clip_to_gamut().approx_eq(in_gamut, 1e-6));
     }
-}
 
+    #[test]
+    fn nearly_black_counts_as_black() {
+        let c = Color::new(0.002, 0.0, -0.001);
+        assert!(c.is_black(0.01));
+        assert!(!c.is_black(0.001));
+        assert!(!Color::new(0.0, 0.05, 0.0).is_black(0.01));
 
-macro_rules! impl_op_real {
-    ($($op:tt, $fn:ident, $trait:ident, $doc:tt);*) => {
-        $(
-            impl<T: Clone + Copy + $trait<T, Output = T>> $trait<T> for Complex<T> {
-                type Output = Self;
+        assert!(c.approx_eq(Color::BLACK, 0.002) && !c.approx_eq(Color::BLACK, 0.0015));
+        assert!(Color::WHITE.approx_eq(Color::WHITE, 0.0));
+    }
 
-                #[inline]
-                #[doc = $doc]
-                fn $fn(self, rhs: T) -> Self::Output {
-                    Self {
-                        re: self.re $op rhs,
-                        im: self.im $op rhs,
-                    }
-                }
-            }
-        )*
+    #[test]
+    fn accumulator_gives_the_weighted_mean() {
+        let mut acc = ColorAccumulator::new();
+        assert!(acc.mean().approx_eq(Color::BLACK, 0.0));
+
+        acc.add_weighted(Color::new(1.0, 0.0, 0.5), 3.0);
+        acc.add_weighted(Color::new(0.0, 1.0, 0.5), 1.0);
+        acc.add_weighted(Color::WHITE, 0.0);
+
+        assert_eq!(acc.weight(), 4.0);
+        assert!(acc.mean().approx_eq(Color::new(0.75, 0.25, 0.5), 1e-6), "{:?}", acc.mean());
+
+        // Scaling every weight leaves the mean unchanged.
+        let mut scaled = ColorAccumulator::new();
+        scaled.add_weighted(Color::new(1.0, 0.0, 0.5), 0.3);
+        scaled.add_weighted(Color::new(0.0, 1.0, 0.5), 0.1);
+        assert!(scaled.mean().approx_eq(acc.mean(), 1e-6), "{:?}", scaled.mean());
     }
 }
-
-impl_op_real!(
-    +, add, Add, "Adds a real number to both the real and imaginary components of this complex number."; 
-    -, sub, Sub, "Subtracts a real number from both the real and imaginary components of this complex number."; 
-    *, mul, Mul, "Multiplies both the real and imaginary components of this complex number by a real number, effectively scaling it."; 
-    /, div, Div, "Divides both the real and imaginary components of this complex number by a real number, effectively scaling it."
-);